@@ -1,7 +1,10 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::configure()
         .protoc_arg("--experimental_allow_proto3_optional")
-        .compile_protos(&["proto/kademlia.proto"], &["proto"])?;
+        .compile_protos(&["proto/kademlia.proto", "proto/ledger.proto", "proto/light.proto", "proto/mining.proto"], &["proto"])?;
     println!("cargo:rerun-if-changed=proto/kademlia.proto");
+    println!("cargo:rerun-if-changed=proto/ledger.proto");
+    println!("cargo:rerun-if-changed=proto/light.proto");
+    println!("cargo:rerun-if-changed=proto/mining.proto");
     Ok(())
 }
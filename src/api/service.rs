@@ -0,0 +1,300 @@
+use crate::auctions::auction::{collect_auctions, find_auction_transactions, Auction};
+use crate::blockchain::block::Block;
+use crate::blockchain::transaction::Transaction;
+use crate::kademlia::node::Node;
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+
+type ApiResult = Result<Response, ApiError>;
+
+struct ApiError {
+    status: StatusCode,
+    message: &'static str,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        json_response(self.status, json!({ "error": self.message }))
+    }
+}
+
+#[derive(Deserialize)]
+struct SubmitTransactionRequest {
+    transaction: Transaction,
+}
+
+pub fn router(node: Node) -> Router {
+    Router::new()
+        .route("/chain", get(get_chain_info))
+        .route("/blocks/:height", get(get_block))
+        .route("/blocks/hash/:hash", get(get_block_by_hash))
+        .route("/transactions", get(get_pool).post(submit_transaction))
+        .route("/transactions/:hash", get(get_transaction))
+        .route("/accounts/:address", get(get_account))
+        .route("/accounts/:address/transactions", get(get_account_transactions))
+        .route("/auctions", get(get_auctions))
+        .route("/auctions/:id", get(get_auction))
+        .with_state(node)
+}
+
+pub async fn serve(node: Node, address: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(address).await?;
+    axum::serve(listener, router(node)).await?;
+    Ok(())
+}
+
+fn json_response(status: StatusCode, value: Value) -> Response {
+    (status, [(header::CONTENT_TYPE, "application/json")], value.to_string()).into_response()
+}
+
+fn ok(value: Value) -> ApiResult {
+    Ok(json_response(StatusCode::OK, value))
+}
+
+fn error(status: StatusCode, message: &'static str) -> ApiError {
+    ApiError {
+        status,
+        message,
+    }
+}
+
+fn not_found(message: &'static str) -> ApiError {
+    error(StatusCode::NOT_FOUND, message)
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, ApiError> {
+    hex::decode(value).map_err(|_| error(StatusCode::BAD_REQUEST, "invalid hex value"))
+}
+
+fn transaction_json(tx: &Transaction) -> Value {
+    json!({
+        "hash": hex::encode(&tx.tx_hash),
+        "type": format!("{:?}", tx.data.tx_type),
+        "chain_id": tx.data.chain_id,
+        "sender": hex::encode(&tx.data.sender),
+        "receiver": tx.data.receiver.as_ref().map(hex::encode),
+        "amount": tx.data.amount,
+        "data": tx.data.data,
+        "nonce": tx.data.nonce,
+        "fee": tx.data.fee,
+        "timestamp": tx.data.timestamp,
+        "valid_until": tx.data.valid_until,
+    })
+}
+
+fn block_json(block: &Block) -> Value {
+    json!({
+        "index": block.index,
+        "hash": hex::encode(&block.hash),
+        "prev_hash": hex::encode(&block.prev_hash),
+        "timestamp": block.timestamp,
+        "nonce": block.nonce,
+        "extra_nonce": block.extra_nonce,
+        "bits": format!("{:08x}", block.bits),
+        "merkle_root": hex::encode(&block.merkle_root),
+        "state_root": hex::encode(&block.state_root),
+        "transactions": block.transactions.iter().map(transaction_json).collect::<Vec<_>>(),
+    })
+}
+
+fn auction_json(auction: &Auction) -> Value {
+    json!({
+        "id": auction.auction_id,
+        "status": format!("{:?}", auction.status),
+        "owner": hex::encode(&auction.owner),
+        "title": auction.title,
+        "description": auction.description,
+        "created_time": auction.created_time,
+        "start_time": auction.start_time,
+        "end_time": auction.end_time,
+        "highest_bid": auction.highest_bid.as_ref().map(|(amount, bidder)| json!({
+            "amount": amount,
+            "bidder": hex::encode(bidder),
+        })),
+    })
+}
+
+async fn get_chain_info(State(node): State<Node>) -> ApiResult {
+    let pool_size = {
+        let pool_lock = node.get_transaction_pool().map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+        let pool = pool_lock.lock().map_err(|_| {
+            error(StatusCode::INTERNAL_SERVER_ERROR, "failed to acquire lock on transaction pool")
+        })?;
+        pool.size()
+    };
+
+    let blockchain_lock = node.get_blockchain().map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+    let blockchain = blockchain_lock.read().map_err(|_| {
+        error(StatusCode::INTERNAL_SERVER_ERROR, "failed to acquire lock on blockchain")
+    })?;
+
+    ok(json!({
+        "chain_id": blockchain.get_chain_id(),
+        "height": blockchain.get_block_height(),
+        "tip": blockchain.get_last_block().map(|block| hex::encode(&block.hash)),
+        "cumulative_work": hex::encode(blockchain.get_cumulative_work().to_be_bytes()),
+        "next_bits": format!("{:08x}", blockchain.get_next_bits()),
+        "finalized_height": blockchain.get_finalized_height(),
+        "pool_size": pool_size,
+    }))
+}
+
+async fn get_block(State(node): State<Node>, Path(height): Path<usize>) -> ApiResult {
+    let blockchain_lock = node.get_blockchain().map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+    let blockchain = blockchain_lock.read().map_err(|_| {
+        error(StatusCode::INTERNAL_SERVER_ERROR, "failed to acquire lock on blockchain")
+    })?;
+
+    let block = blockchain.blocks.get(height).ok_or_else(|| not_found("block not found"))?;
+    ok(block_json(block))
+}
+
+async fn get_block_by_hash(State(node): State<Node>, Path(hash): Path<String>) -> ApiResult {
+    let hash = decode_hex(&hash)?;
+
+    let blockchain_lock = node.get_blockchain().map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+    let blockchain = blockchain_lock.read().map_err(|_| {
+        error(StatusCode::INTERNAL_SERVER_ERROR, "failed to acquire lock on blockchain")
+    })?;
+
+    let block = blockchain.get_block_by_hash(&hash).ok_or_else(|| not_found("block not found"))?;
+    ok(block_json(block))
+}
+
+async fn get_transaction(State(node): State<Node>, Path(hash): Path<String>) -> ApiResult {
+    let hash = decode_hex(&hash)?;
+
+    {
+        let blockchain_lock = node.get_blockchain().map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+        let blockchain = blockchain_lock.read().map_err(|_| {
+            error(StatusCode::INTERNAL_SERVER_ERROR, "failed to acquire lock on blockchain")
+        })?;
+
+        if let (Some(location), Some(tx)) = (blockchain.get_transaction_location(&hash), blockchain.get_transaction(&hash)) {
+            return ok(json!({
+                "status": "confirmed",
+                "height": location.height,
+                "position": location.position,
+                "transaction": transaction_json(tx),
+            }));
+        }
+    }
+
+    let pool_lock = node.get_transaction_pool().map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+    let pool = pool_lock.lock().map_err(|_| {
+        error(StatusCode::INTERNAL_SERVER_ERROR, "failed to acquire lock on transaction pool")
+    })?;
+
+    let tx = pool.get_transaction(&hash).ok_or_else(|| not_found("transaction not found"))?;
+    ok(json!({
+        "status": "pending",
+        "transaction": transaction_json(tx),
+    }))
+}
+
+async fn get_pool(State(node): State<Node>) -> ApiResult {
+    let pool_lock = node.get_transaction_pool().map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+    let pool = pool_lock.lock().map_err(|_| {
+        error(StatusCode::INTERNAL_SERVER_ERROR, "failed to acquire lock on transaction pool")
+    })?;
+
+    let mut transactions = pool.get_all_transactions();
+    transactions.sort_by(|a, b| (&a.data.sender, a.data.nonce).cmp(&(&b.data.sender, b.data.nonce)));
+
+    ok(json!(transactions.into_iter().map(transaction_json).collect::<Vec<_>>()))
+}
+
+async fn submit_transaction(State(node): State<Node>, body: Bytes) -> ApiResult {
+    let request: SubmitTransactionRequest = serde_json::from_slice(&body)
+        .map_err(|_| error(StatusCode::BAD_REQUEST, "invalid transaction request"))?;
+
+    let tx_hash = hex::encode(&request.transaction.tx_hash);
+    node.submit_transaction(request.transaction)
+        .await
+        .map_err(|e| error(StatusCode::UNPROCESSABLE_ENTITY, e))?;
+
+    Ok(json_response(StatusCode::ACCEPTED, json!({ "hash": tx_hash })))
+}
+
+async fn get_account(State(node): State<Node>, Path(address): Path<String>) -> ApiResult {
+    let address = decode_hex(&address)?;
+
+    let pending_nonce = {
+        let pool_lock = node.get_transaction_pool().map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+        let pool = pool_lock.lock().map_err(|_| {
+            error(StatusCode::INTERNAL_SERVER_ERROR, "failed to acquire lock on transaction pool")
+        })?;
+        pool.get_pending_by_sender(&address).iter().map(|tx| tx.data.nonce).max()
+    };
+
+    let blockchain_lock = node.get_blockchain().map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+    let blockchain = blockchain_lock.read().map_err(|_| {
+        error(StatusCode::INTERNAL_SERVER_ERROR, "failed to acquire lock on blockchain")
+    })?;
+
+    let state = blockchain.get_state();
+    let next_nonce = pending_nonce.map_or(state.get_next_nonce(&address), |nonce| {
+        (nonce + 1).max(state.get_next_nonce(&address))
+    });
+
+    ok(json!({
+        "address": hex::encode(&address),
+        "balance": state.get_balance(&address),
+        "nonce": state.get_nonce(&address),
+        "next_nonce": next_nonce,
+    }))
+}
+
+async fn get_account_transactions(State(node): State<Node>, Path(address): Path<String>) -> ApiResult {
+    let address = decode_hex(&address)?;
+
+    let blockchain_lock = node.get_blockchain().map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+    let blockchain = blockchain_lock.read().map_err(|_| {
+        error(StatusCode::INTERNAL_SERVER_ERROR, "failed to acquire lock on blockchain")
+    })?;
+
+    let transactions = blockchain
+        .get_address_transactions(&address)
+        .into_iter()
+        .map(transaction_json)
+        .collect::<Vec<_>>();
+
+    ok(json!(transactions))
+}
+
+async fn get_auctions(State(node): State<Node>) -> ApiResult {
+    let blockchain_lock = node.get_blockchain().map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+    let blockchain = blockchain_lock.read().map_err(|_| {
+        error(StatusCode::INTERNAL_SERVER_ERROR, "failed to acquire lock on blockchain")
+    })?;
+
+    let auction_txs: Vec<Transaction> = find_auction_transactions(&blockchain).into_iter().cloned().collect();
+    let mut auctions: Vec<Auction> = collect_auctions(&auction_txs).into_values().collect();
+    auctions.sort_by(|a, b| (a.created_time, &a.auction_id).cmp(&(b.created_time, &b.auction_id)));
+
+    ok(json!(auctions.iter().map(auction_json).collect::<Vec<_>>()))
+}
+
+async fn get_auction(State(node): State<Node>, Path(id): Path<String>) -> ApiResult {
+    let blockchain_lock = node.get_blockchain().map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+    let blockchain = blockchain_lock.read().map_err(|_| {
+        error(StatusCode::INTERNAL_SERVER_ERROR, "failed to acquire lock on blockchain")
+    })?;
+
+    let auction_txs: Vec<Transaction> = blockchain.get_auction_transactions(&id).into_iter().cloned().collect();
+    let auction = collect_auctions(&auction_txs)
+        .remove(&id)
+        .ok_or_else(|| not_found("auction not found"))?;
+
+    let mut value = auction_json(&auction);
+    value["transactions"] = json!(auction_txs.iter().map(transaction_json).collect::<Vec<_>>());
+    ok(value)
+}
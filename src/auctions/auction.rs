@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::auctions::auction_commands::AuctionCommand;
-use crate::blockchain::{blockchain::Blockchain, transaction::{Transaction, TransactionType}};
+use crate::blockchain::{blockchain::Blockchain, transaction::Transaction};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum AuctionStatus {
@@ -24,45 +24,14 @@ pub struct Auction {
 }
 
 pub fn find_auction_transactions(blockchain: &Blockchain) -> Vec<&Transaction> {
-    let mut auction_txs = Vec::new();
-
-    for block in &blockchain.blocks {
-        for tx in &block.transactions {
-            if tx.data.tx_type != TransactionType::Data { continue; }
-
-            if let Some(data) = &tx.data.data {
-                if data.starts_with("AUCTION_") {
-                    auction_txs.push(tx);
-                }
-            }
-        }
-    }
-
-    auction_txs
-}
-
-fn parse_auction_command(data: &str) -> Option<AuctionCommand> {
-    if let Some(stripped) = data.strip_prefix("AUCTION_") {
-        serde_json::from_str(stripped).ok()
-    } else {
-        None
-    }
+    blockchain.get_all_auction_transactions()
 }
 
 pub fn collect_auctions(transactions: &[Transaction]) -> HashMap<String, Auction> {
     let mut auctions: HashMap<String, Auction> = HashMap::new();
 
     for tx in transactions {
-        if tx.data.tx_type != TransactionType::Data {
-            continue;
-        }
-
-        let Some(data) = &tx.data.data else { continue };
-        if !data.starts_with("AUCTION_") {
-            continue;
-        }
-
-        let Some(command) = parse_auction_command(data) else { continue };
+        let Some(command) = tx.get_auction_command() else { continue };
 
         match command {
             AuctionCommand::CreateAuction { id, title, description } => {
@@ -1,36 +1,12 @@
 use crate::blockchain::transaction::Transaction;
 use ed25519_dalek::Keypair;
-use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-#[derive(Serialize, Deserialize, Debug)]
-pub enum AuctionCommand {
-    CreateAuction {
-        id: String,
-        title: String,
-        description: String,
-    },
-    StartAuction {
-        id: String,
-    },
-    EndAuction {
-        id: String,
-    },
-    Bid {
-        id: String,
-        amount: u64,
-    },
-}
-
-impl AuctionCommand {
-    pub fn to_data_string(&self) -> Result<String, serde_json::Error> {
-        let serialized = serde_json::to_string(self)?;
-        Ok(format!("AUCTION_{}", serialized))
-    }
-}
+pub use crate::blockchain::auction_command::AuctionCommand;
 
 pub fn create_auction_tx(
     key_pair: &Keypair,
+    chain_id: &str,
     command: AuctionCommand,
     nonce: u64,
 ) -> Result<Transaction, &'static str> {
@@ -38,7 +14,7 @@ pub fn create_auction_tx(
         .to_data_string()
         .map_err(|_| "Failed to serialize auction command")?;
 
-    Transaction::create_data_tx(key_pair, data, nonce, 0)
+    Transaction::create_data_tx(key_pair, chain_id, data, nonce, 0)
 }
 
 pub fn generate_auction_id(public_key: &[u8], title: &str, description: &str, nonce: u64) -> String {
@@ -46,13 +22,14 @@ pub fn generate_auction_id(public_key: &[u8], title: &str, description: &str, no
     hasher.update(public_key);
     hasher.update(title.as_bytes());
     hasher.update(description.as_bytes());
-    hasher.update(&nonce.to_le_bytes());
+    hasher.update(nonce.to_le_bytes());
 
     format!("{:x}", hasher.finalize())[..16].to_string()
 }
 
 pub fn tx_create_auction(
     key_pair: &Keypair,
+    chain_id: &str,
     title: String,
     description: String,
     nonce: u64,
@@ -63,30 +40,33 @@ pub fn tx_create_auction(
         title,
         description,
     };
-    create_auction_tx(key_pair, command, nonce)
+    create_auction_tx(key_pair, chain_id, command, nonce)
 }
 
 pub fn tx_start_auction(
     key_pair: &Keypair,
+    chain_id: &str,
     id: String,
     nonce: u64,
 ) -> Result<Transaction, &'static str> {
-    create_auction_tx(key_pair, AuctionCommand::StartAuction { id }, nonce)
+    create_auction_tx(key_pair, chain_id, AuctionCommand::StartAuction { id }, nonce)
 }
 
 pub fn tx_end_auction(
     key_pair: &Keypair,
+    chain_id: &str,
     id: String,
     nonce: u64,
 ) -> Result<Transaction, &'static str> {
-    create_auction_tx(key_pair, AuctionCommand::EndAuction { id }, nonce)
+    create_auction_tx(key_pair, chain_id, AuctionCommand::EndAuction { id }, nonce)
 }
 
 pub fn tx_bid(
     key_pair: &Keypair,
+    chain_id: &str,
     id: String,
     amount: u64,
     nonce: u64,
 ) -> Result<Transaction, &'static str> {
-    create_auction_tx(key_pair, AuctionCommand::Bid { id, amount }, nonce)
+    create_auction_tx(key_pair, chain_id, AuctionCommand::Bid { id, amount }, nonce)
 }
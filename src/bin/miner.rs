@@ -0,0 +1,73 @@
+use ledger::blockchain::block::BlockHeader;
+use ledger::blockchain::miner::Miner;
+use ledger::mining::mining_proto::mining_client::MiningClient;
+use ledger::mining::mining_proto::{GetWorkRequest, SubmitWorkRequest};
+use std::env;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::Request;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.is_empty() || args.len() > 2 {
+        eprintln!("Usage: cargo run --bin miner <NODE PORT> [THREADS]");
+        std::process::exit(1);
+    }
+
+    let addr = format!("http://127.0.0.1:{}", args[0]);
+    let miner = Arc::new(match args.get(1) {
+        Some(threads) => Miner::new(threads.parse()?),
+        None => Miner::default(),
+    });
+
+    let mut client = MiningClient::connect(addr.clone()).await?;
+    println!("Connected to {}", addr);
+
+    loop {
+        let work = match client.get_work(Request::new(GetWorkRequest {})).await {
+            Ok(response) => response.into_inner(),
+            Err(e) => {
+                eprintln!("Failed to get work: {}", e.message());
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+
+        let Some(header) = work.header else {
+            continue;
+        };
+        let header: BlockHeader = header.into();
+
+        let miner = miner.clone();
+        let search_header = header.clone();
+        let nonce = tokio::task::spawn_blocking(move || {
+            miner.search(&search_header, &AtomicBool::new(false))
+        }).await?;
+
+        let Some(nonce) = nonce else {
+            continue;
+        };
+
+        let request = Request::new(SubmitWorkRequest {
+            template_id: work.template_id,
+            extra_nonce: header.extra_nonce,
+            timestamp: header.timestamp as u64,
+            nonce,
+        });
+
+        match client.submit_work(request).await {
+            Ok(response) => {
+                let response = response.into_inner();
+                if response.accepted {
+                    println!("Block {} accepted: {}", header.index, hex::encode(&response.block_hash));
+                } else {
+                    eprintln!("Block {} rejected: {}", header.index, response.error);
+                }
+            }
+            Err(e) => eprintln!("Failed to submit work: {}", e.message()),
+        }
+    }
+}
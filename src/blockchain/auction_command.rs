@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+const AUCTION_PREFIX: &str = "AUCTION_";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum AuctionCommand {
+    CreateAuction {
+        id: String,
+        title: String,
+        description: String,
+    },
+    StartAuction {
+        id: String,
+    },
+    EndAuction {
+        id: String,
+    },
+    Bid {
+        id: String,
+        amount: u64,
+    },
+}
+
+impl AuctionCommand {
+    pub fn parse(data: &str) -> Option<Self> {
+        serde_json::from_str(data.strip_prefix(AUCTION_PREFIX)?).ok()
+    }
+
+    pub fn to_data_string(&self) -> Result<String, serde_json::Error> {
+        let serialized = serde_json::to_string(self)?;
+        Ok(format!("{}{}", AUCTION_PREFIX, serialized))
+    }
+
+    pub fn get_id(&self) -> &str {
+        match self {
+            AuctionCommand::CreateAuction { id, .. }
+            | AuctionCommand::StartAuction { id }
+            | AuctionCommand::EndAuction { id }
+            | AuctionCommand::Bid { id, .. } => id,
+        }
+    }
+}
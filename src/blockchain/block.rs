@@ -1,38 +1,55 @@
 use super::*;
-use crate::blockchain::lib::{u128_to_bytes, u32_to_bytes, u64_to_bytes, BHash};
+use crate::blockchain::encoding::{canonical_serde, Decode, Decoder, Encode, Encoder};
+use crate::blockchain::genesis::GenesisConfig;
+use crate::blockchain::lib::BHash;
 use crate::blockchain::merkle_tree::MerkleTree;
+use crate::blockchain::state::ChainState;
 use crate::blockchain::transaction::Transaction;
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Debug, Formatter};
 use std::vec;
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
 pub struct Block {
     pub index: u32,
     pub timestamp: u128,
     pub hash: BHash,
     pub prev_hash: BHash,
     pub nonce: u64,
+    pub extra_nonce: u64,
+    pub bits: u32,
     pub merkle_root: BHash,
+    pub state_root: BHash,
     pub transactions: Vec<Transaction>,
     pub tx_count: u32,
 }
 
+canonical_serde!(Block);
+
+pub trait ChainHeader {
+    fn index(&self) -> u32;
+    fn timestamp(&self) -> u128;
+    fn bits(&self) -> u32;
+    fn block_hash(&self) -> &[u8];
+    fn prev_hash(&self) -> &[u8];
+}
+
 impl Debug for Block {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "Block[{}]: {} at: {} with: {} {}",
+        write!(f, "Block[{}]: {} at: {} with: {} {} bits: {:08x}",
                &self.index,
                &hex::encode(&self.hash),
                &self.timestamp,
                &self.tx_count,
                &hex::encode(&self.merkle_root),
+               &self.bits,
         )
     }
 }
 
 impl Block {
     pub fn new(index: u32, timestamp: u128, prev_hash: BHash,
-               nonce: u64, transactions: Vec<Transaction>) -> Self {
+               nonce: u64, bits: u32, transactions: Vec<Transaction>) -> Self {
         let merkle_tree = MerkleTree::new(&transactions);
         let merkle_root = merkle_tree.get_root_hash().unwrap_or_else(|| vec![0; 32]);
         let tx_count = transactions.len() as u32;
@@ -42,23 +59,31 @@ impl Block {
             hash: vec![0; 32],
             prev_hash,
             nonce,
+            extra_nonce: 0,
+            bits,
             merkle_root,
+            state_root: vec![0; 32],
             transactions,
             tx_count,
         }
     }
 
-    pub fn genesis() -> Self {
-        Block {
+    pub fn genesis(config: &GenesisConfig) -> Self {
+        let mut block = Block {
             index: 0,
-            timestamp: lib::now(),
+            timestamp: config.timestamp,
             hash: vec![0; 32],
             prev_hash: vec![0; 32],
             nonce: 0,
-            merkle_root: vec![0; 32],
+            extra_nonce: 0,
+            bits: config.bits,
+            merkle_root: config.hash(),
+            state_root: ChainState::new(config.get_balances().unwrap_or_default()).get_root(),
             transactions: Vec::new(),
             tx_count: 0,
-        }
+        };
+        block.hash = block.hash();
+        block
     }
 
     pub fn get_transaction(&self, tx_hash: &[u8]) -> Option<&Transaction> {
@@ -81,7 +106,10 @@ impl Block {
             hash: self.hash.clone(),
             prev_hash: self.prev_hash.clone(),
             nonce: self.nonce,
+            extra_nonce: self.extra_nonce,
+            bits: self.bits,
             merkle_root: self.merkle_root.clone(),
+            state_root: self.state_root.clone(),
             tx_count: self.tx_count,
         }
     }
@@ -94,36 +122,130 @@ pub struct BlockHeader {
     pub hash: BHash,
     pub prev_hash: BHash,
     pub nonce: u64,
+    pub extra_nonce: u64,
+    pub bits: u32,
     pub merkle_root: BHash,
+    pub state_root: BHash,
     pub tx_count: u32,
 }
 
+impl ChainHeader for Block {
+    fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn timestamp(&self) -> u128 {
+        self.timestamp
+    }
+
+    fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    fn block_hash(&self) -> &[u8] {
+        &self.hash
+    }
+
+    fn prev_hash(&self) -> &[u8] {
+        &self.prev_hash
+    }
+}
+
+impl ChainHeader for BlockHeader {
+    fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn timestamp(&self) -> u128 {
+        self.timestamp
+    }
+
+    fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    fn block_hash(&self) -> &[u8] {
+        &self.hash
+    }
+
+    fn prev_hash(&self) -> &[u8] {
+        &self.prev_hash
+    }
+}
+
 impl Hashable for Block {
     fn bytes(&self) -> Vec<u8> {
-        let mut bytes = vec![];
+        self.get_header().bytes()
+    }
+}
 
-        bytes.extend(&u32_to_bytes(&self.index));
-        bytes.extend(&u128_to_bytes(&self.timestamp));
-        bytes.extend(&self.prev_hash);
-        bytes.extend(&u64_to_bytes(&self.nonce));
-        bytes.extend(&self.merkle_root);
-        bytes.extend(&u32_to_bytes(&self.tx_count));
+impl Encode for Block {
+    fn encode(&self, encoder: &mut Encoder) {
+        self.get_header().encode(encoder);
+        encoder.write_seq(&self.transactions);
+    }
+}
+
+impl Decode for Block {
+    fn decode(decoder: &mut Decoder) -> Result<Self, &'static str> {
+        Ok(Block {
+            index: decoder.read_u32()?,
+            timestamp: decoder.read_u128()?,
+            hash: decoder.read_bytes()?,
+            prev_hash: decoder.read_bytes()?,
+            nonce: decoder.read_u64()?,
+            extra_nonce: decoder.read_u64()?,
+            bits: decoder.read_u32()?,
+            merkle_root: decoder.read_bytes()?,
+            state_root: decoder.read_bytes()?,
+            tx_count: decoder.read_u32()?,
+            transactions: decoder.read_seq()?,
+        })
+    }
+}
 
-        bytes
+impl BlockHeader {
+    fn encode_with_hash(&self, encoder: &mut Encoder, hash: &[u8]) {
+        encoder.write_u32(self.index);
+        encoder.write_u128(self.timestamp);
+        encoder.write_bytes(hash);
+        encoder.write_bytes(&self.prev_hash);
+        encoder.write_u64(self.nonce);
+        encoder.write_u64(self.extra_nonce);
+        encoder.write_u32(self.bits);
+        encoder.write_bytes(&self.merkle_root);
+        encoder.write_bytes(&self.state_root);
+        encoder.write_u32(self.tx_count);
     }
 }
 
 impl Hashable for BlockHeader {
     fn bytes(&self) -> Vec<u8> {
-        let mut bytes = vec![];
+        let mut encoder = Encoder::new();
+        self.encode_with_hash(&mut encoder, &[]);
+        encoder.into_bytes()
+    }
+}
 
-        bytes.extend(&u32_to_bytes(&self.index));
-        bytes.extend(&u128_to_bytes(&self.timestamp));
-        bytes.extend(&self.prev_hash);
-        bytes.extend(&u64_to_bytes(&self.nonce));
-        bytes.extend(&self.merkle_root);
-        bytes.extend(&u32_to_bytes(&self.tx_count));
+impl Encode for BlockHeader {
+    fn encode(&self, encoder: &mut Encoder) {
+        self.encode_with_hash(encoder, &self.hash);
+    }
+}
 
-        bytes
+impl Decode for BlockHeader {
+    fn decode(decoder: &mut Decoder) -> Result<Self, &'static str> {
+        Ok(BlockHeader {
+            index: decoder.read_u32()?,
+            timestamp: decoder.read_u128()?,
+            hash: decoder.read_bytes()?,
+            prev_hash: decoder.read_bytes()?,
+            nonce: decoder.read_u64()?,
+            extra_nonce: decoder.read_u64()?,
+            bits: decoder.read_u32()?,
+            merkle_root: decoder.read_bytes()?,
+            state_root: decoder.read_bytes()?,
+            tx_count: decoder.read_u32()?,
+        })
     }
 }
@@ -0,0 +1,146 @@
+use crate::blockchain::block::{Block, BlockHeader};
+use crate::blockchain::lib::BHash;
+use crate::blockchain::target::{work_from_bits, U256};
+use crate::constants::MAX_ORPHAN_BLOCKS;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Clone)]
+pub struct BlockIndexEntry {
+    pub header: BlockHeader,
+    pub cumulative_work: U256,
+}
+
+#[derive(Clone, Default)]
+pub struct BlockTree {
+    entries: HashMap<BHash, BlockIndexEntry>,
+    side_blocks: HashMap<BHash, Block>,
+    orphans: HashMap<BHash, Block>,
+    orphan_order: VecDeque<BHash>,
+}
+
+impl BlockTree {
+    pub fn from_chain(chain: &[Block]) -> Self {
+        let mut tree = BlockTree::default();
+        for block in chain {
+            tree.insert_header(block.get_header());
+        }
+        tree
+    }
+
+    pub fn contains(&self, hash: &[u8]) -> bool {
+        self.entries.contains_key(hash)
+    }
+
+    pub fn is_orphan(&self, hash: &[u8]) -> bool {
+        self.orphans.contains_key(hash)
+    }
+
+    pub fn get(&self, hash: &[u8]) -> Option<&BlockIndexEntry> {
+        self.entries.get(hash)
+    }
+
+    pub fn get_work(&self, hash: &[u8]) -> U256 {
+        self.entries.get(hash).map_or(U256::ZERO, |entry| entry.cumulative_work)
+    }
+
+    fn insert_header(&mut self, header: BlockHeader) {
+        let cumulative_work = self.get_work(&header.prev_hash).saturating_add(&work_from_bits(header.bits));
+        self.entries.insert(header.hash.clone(), BlockIndexEntry {
+            header,
+            cumulative_work,
+        });
+    }
+
+    pub fn insert_side_block(&mut self, block: Block) {
+        self.insert_header(block.get_header());
+        self.side_blocks.insert(block.hash.clone(), block);
+    }
+
+    pub fn connect_block(&mut self, block: &Block) {
+        if !self.contains(&block.hash) {
+            self.insert_header(block.get_header());
+        }
+        self.side_blocks.remove(&block.hash);
+    }
+
+    pub fn disconnect_block(&mut self, block: Block) {
+        self.side_blocks.insert(block.hash.clone(), block);
+    }
+
+    pub fn remove_branch(&mut self, hash: &[u8]) {
+        let mut invalid = vec![hash.to_vec()];
+        while let Some(hash) = invalid.pop() {
+            self.side_blocks.remove(&hash);
+            self.entries.remove(&hash);
+            invalid.extend(
+                self.side_blocks
+                    .values()
+                    .filter(|block| block.prev_hash == hash)
+                    .map(|block| block.hash.clone()),
+            );
+        }
+    }
+
+    pub fn get_best_side_tip(&self) -> Option<&BlockIndexEntry> {
+        self.side_blocks
+            .keys()
+            .filter_map(|hash| self.entries.get(hash))
+            .max_by_key(|entry| entry.cumulative_work)
+    }
+
+    pub fn get_ancestors(&self, hash: &[u8], count: usize) -> Vec<BlockHeader> {
+        let mut ancestors = Vec::with_capacity(count);
+        let mut current = self.entries.get(hash);
+
+        while let Some(entry) = current {
+            if ancestors.len() >= count {
+                break;
+            }
+            ancestors.push(entry.header.clone());
+            current = self.entries.get(&entry.header.prev_hash);
+        }
+
+        ancestors.reverse();
+        ancestors
+    }
+
+    pub fn get_branch(&self, tip: &[u8], is_connected: impl Fn(&BlockHeader) -> bool) -> Option<(usize, Vec<Block>)> {
+        let mut branch = Vec::new();
+        let mut current = self.entries.get(tip)?;
+
+        while !is_connected(&current.header) {
+            branch.push(self.side_blocks.get(&current.header.hash)?.clone());
+            current = self.entries.get(&current.header.prev_hash)?;
+        }
+
+        branch.reverse();
+        Some((current.header.index as usize, branch))
+    }
+
+    pub fn add_orphan(&mut self, block: Block) {
+        if self.is_orphan(&block.hash) {
+            return;
+        }
+
+        if self.orphans.len() >= MAX_ORPHAN_BLOCKS {
+            if let Some(oldest) = self.orphan_order.pop_front() {
+                self.orphans.remove(&oldest);
+            }
+        }
+
+        self.orphan_order.push_back(block.hash.clone());
+        self.orphans.insert(block.hash.clone(), block);
+    }
+
+    pub fn take_orphans(&mut self, parent: &[u8]) -> Vec<Block> {
+        let hashes: Vec<BHash> = self
+            .orphans
+            .values()
+            .filter(|block| block.prev_hash == parent)
+            .map(|block| block.hash.clone())
+            .collect();
+
+        self.orphan_order.retain(|hash| !hashes.contains(hash));
+        hashes.iter().filter_map(|hash| self.orphans.remove(hash)).collect()
+    }
+}
@@ -1,186 +1,343 @@
 use super::*;
-use crate::blockchain::block::Block;
+use crate::blockchain::block::{Block, BlockHeader, ChainHeader};
+use crate::blockchain::block_tree::BlockTree;
+use crate::blockchain::difficulty::next_bits;
+use crate::blockchain::genesis::GenesisConfig;
+use crate::blockchain::index::{ChainIndex, TxLocation};
 use crate::blockchain::lib::{now, BHash};
+use crate::blockchain::listener::ChainListener;
 use crate::blockchain::merkle_tree::{MerkleProof, MerkleTree};
-use crate::blockchain::transaction::{PublicKey, Transaction, TransactionType};
-use crate::constants::{DIFFICULTY_PREFIX, MAX_BLOCK_TIME, MAX_FORK_DEPTH, MAX_MINING_TIME, MIN_BLOCK_TIME};
+use crate::blockchain::state::{AccountProof, ChainState};
+use crate::blockchain::storage::BlockStore;
+use crate::blockchain::target::{hash_meets_target, work_from_bits, U256};
+use crate::blockchain::template::BlockTemplate;
+use crate::blockchain::transaction::{PublicKey, Transaction};
+use ed25519_dalek::Keypair;
+use crate::constants::{FINALITY_DEPTH, MAX_BLOCK_TIME, MAX_FUTURE_BLOCK_TIME, MAX_RETARGET_FACTOR, MAX_STATE_SNAPSHOTS, MIN_BLOCK_TIME, RETARGET_INTERVAL, STATE_SNAPSHOT_INTERVAL};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
-use std::time::Instant;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use std::vec;
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize)]
 pub struct Blockchain {
+    pub genesis: GenesisConfig,
     pub blocks: Vec<Block>,
-    pub difficulty: usize,
-    pub forks: HashMap<BHash, Vec<Block>>,
-    pub balances: HashMap<Vec<u8>, u64>,
+    pub block_subsidy: u64,
+    #[serde(skip)]
+    tree: BlockTree,
+    #[serde(skip)]
+    index: ChainIndex,
+    #[serde(skip)]
+    checkpoints: HashMap<u32, BHash>,
+    #[serde(skip)]
+    state: ChainState,
+    #[serde(skip)]
+    snapshots: BTreeMap<usize, ChainState>,
+    #[serde(skip)]
+    store: Option<Arc<Mutex<dyn BlockStore>>>,
+    #[serde(skip)]
+    listeners: Vec<Arc<Mutex<dyn ChainListener>>>,
+}
+
+impl Clone for Blockchain {
+    fn clone(&self) -> Self {
+        Blockchain {
+            genesis: self.genesis.clone(),
+            blocks: self.blocks.clone(),
+            tree: self.tree.clone(),
+            index: self.index.clone(),
+            checkpoints: self.checkpoints.clone(),
+            state: self.state.clone(),
+            snapshots: self.snapshots.clone(),
+            block_subsidy: self.block_subsidy,
+            store: None,
+            listeners: Vec::new(),
+        }
+    }
+}
+
+impl Default for Blockchain {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Blockchain {
     pub fn new() -> Self {
+        Self::from_genesis(GenesisConfig::shared().clone())
+    }
+
+    pub fn from_genesis(genesis: GenesisConfig) -> Self {
+        let blocks = vec![Block::genesis(&genesis)];
         let mut chain = Blockchain {
-            blocks: Vec::new(),
-            difficulty: DIFFICULTY_PREFIX.len(),
-            forks: HashMap::new(),
-            balances: HashMap::new(),
+            tree: BlockTree::from_chain(&blocks),
+            index: ChainIndex::from_chain(&blocks),
+            blocks,
+            checkpoints: genesis.get_checkpoints().unwrap_or_default(),
+            block_subsidy: genesis.block_subsidy,
+            state: ChainState::default(),
+            snapshots: BTreeMap::new(),
+            store: None,
+            listeners: Vec::new(),
+            genesis,
         };
-        chain.create_genesis_block();
+        chain.reset_state();
         chain
     }
 
-    fn create_genesis_block(&mut self) {
-        let genesis_block = Block::genesis();
-        let hash = genesis_block.hash();
-        let mut genesis = genesis_block;
-        genesis.hash = hash;
-        self.blocks.push(genesis);
-    }
+    pub fn with_store<S: BlockStore + 'static>(store: S) -> Result<Self, &'static str> {
+        let stored_blocks = store.load_all().map_err(|_| "Failed to load blocks from store")?;
 
-    pub fn create_block(&self, transactions: Vec<Transaction>) -> Result<Block, &'static str> {
-        let last_block = self.get_last_block()
-            .ok_or("No blocks in chain")?;
+        let mut chain = Blockchain::new();
+        if !stored_blocks.is_empty() {
+            if stored_blocks[0].hash != chain.get_genesis_hash() {
+                return Err("Stored blockchain belongs to a different genesis");
+            }
+            for block in stored_blocks.into_iter().skip(1) {
+                if chain.add_block(block).is_err() {
+                    break;
+                }
+            }
+        }
 
-        let new_block = Block::new(
-            last_block.index + 1,
-            now(),
-            last_block.hash.clone(),
-            0,
-            transactions,
-        );
+        let stored_height = store.height().min(chain.blocks.len());
+        chain.store = Some(Arc::new(Mutex::new(store)));
+        chain.persist_from(stored_height)?;
 
-        Ok(new_block)
+        Ok(chain)
     }
 
-    pub fn mine_block(&self, block: &mut Block) -> Result<(), &'static str> {
-        let start_time = Instant::now();
-        let target = "0".repeat(self.difficulty);
+    fn persist_block(&self, block: &Block) -> Result<(), &'static str> {
+        if let Some(store) = &self.store {
+            let mut store = store.lock().map_err(|_| "Failed to acquire lock on block store")?;
+            store.append(block).map_err(|_| "Failed to persist block")?;
+        }
+        Ok(())
+    }
 
-        loop {
-            if start_time.elapsed() > MAX_MINING_TIME {
-                return Err("Mining timed out - adjust difficulty");
+    fn persist_from(&self, height: usize) -> Result<(), &'static str> {
+        if let Some(store) = &self.store {
+            let mut store = store.lock().map_err(|_| "Failed to acquire lock on block store")?;
+            store.truncate(height).map_err(|_| "Failed to persist blocks")?;
+            for block in self.blocks.iter().skip(height) {
+                store.append(block).map_err(|_| "Failed to persist blocks")?;
             }
+        }
+        Ok(())
+    }
 
-            block.hash = block.hash();
-            let hash_str = hex::encode(&block.hash);
+    pub fn add_listener(&mut self, listener: Arc<Mutex<dyn ChainListener>>) {
+        self.listeners.push(listener);
+    }
 
-            if hash_str.starts_with(&target) {
-                return Ok(());
+    fn notify_listeners(&self, disconnected: &[Block], connected: &[Block]) {
+        for listener in &self.listeners {
+            if let Ok(mut listener) = listener.lock() {
+                listener.on_chain_update(disconnected, connected, &self.state);
             }
+        }
+    }
 
-            block.nonce += 1;
+    pub fn get_genesis_hash(&self) -> BHash {
+        Block::genesis(&self.genesis).hash
+    }
 
-            if block.nonce == u64::MAX {
-                return Err("Nonce overflow - difficulty too high");
-            }
+    pub fn get_chain_id(&self) -> &str {
+        &self.genesis.chain_id
+    }
+
+    fn reset_state(&mut self) {
+        self.state = self.genesis_state();
+        self.snapshots = BTreeMap::from([(0, self.state.clone())]);
+    }
+
+    fn prune_snapshots(&mut self) {
+        while self.snapshots.len() > MAX_STATE_SNAPSHOTS {
+            self.snapshots.pop_first();
         }
     }
 
-    pub fn add_block(&mut self, block: Block) -> Result<(), &'static str> {
-        self.validate_block(&block)?;
-        self.process_block_transactions(&block)?;
-        self.blocks.push(block);
-        Ok(())
+    fn genesis_state(&self) -> ChainState {
+        ChainState::new(self.genesis.get_balances().unwrap_or_default())
+    }
+
+    pub fn get_state(&self) -> &ChainState {
+        &self.state
     }
 
-    fn validate_block(&self, block: &Block) -> Result<(), &'static str> {
+    pub fn create_block(&self, miner: &Keypair, transactions: Vec<Transaction>) -> Result<Block, &'static str> {
         let last_block = self.get_last_block()
             .ok_or("No blocks in chain")?;
 
-        if block.prev_hash != last_block.hash {
-            return Err("Block has invalid previous hash");
-        }
+        let fees = Self::total_fees(&transactions).ok_or("Transaction fees overflow")?;
+        let reward = self.block_subsidy.checked_add(fees).ok_or("Block reward overflow")?;
+        let coinbase = Transaction::create_coinbase(miner, self.get_chain_id(), last_block.index + 1, reward);
 
-        if block.index != last_block.index + 1 {
-            return Err("Block has invalid index");
-        }
+        let mut block_transactions = Vec::with_capacity(transactions.len() + 1);
+        block_transactions.push(coinbase);
+        block_transactions.extend(transactions);
+
+        let mut new_block = Block::new(
+            last_block.index + 1,
+            now(),
+            last_block.hash.clone(),
+            0,
+            self.get_next_bits(),
+            block_transactions,
+        );
+
+        let mut state = self.state.clone();
+        state.apply_block(&new_block)?;
+        new_block.state_root = state.get_root();
 
-        let time_diff = block.timestamp.saturating_sub(last_block.timestamp);
-        if time_diff < MIN_BLOCK_TIME {
-            return Err("Block time is too short");
+        Ok(new_block)
+    }
+
+    pub fn create_template(&self, miner: &Keypair, transactions: Vec<Transaction>) -> Result<BlockTemplate, &'static str> {
+        let parent_timestamp = self.get_last_block()
+            .ok_or("No blocks in chain")?
+            .timestamp;
+        let block = self.create_block(miner, transactions)?;
+        Ok(BlockTemplate::new(block, parent_timestamp))
+    }
+
+    pub fn add_block(&mut self, block: Block) -> Result<(), &'static str> {
+        let state = self.validate_block(&block, &self.blocks, &self.state)?;
+        self.persist_block(&block)?;
+        self.state = state;
+        self.tree.connect_block(&block);
+        self.index.connect_block(&block);
+        self.notify_listeners(&[], std::slice::from_ref(&block));
+        if (block.index as usize).is_multiple_of(STATE_SNAPSHOT_INTERVAL) {
+            self.snapshots.insert(block.index as usize, self.state.clone());
+            self.prune_snapshots();
         }
-        if time_diff > MAX_BLOCK_TIME {
-            return Err("Block time is too long");
+        self.blocks.push(block);
+        Ok(())
+    }
+
+    fn validate_block(&self, block: &Block, chain: &[Block], state: &ChainState) -> Result<ChainState, &'static str> {
+        validate_header(block, chain)?;
+        self.check_checkpoint(block)?;
+
+        if block.tx_count as usize != block.transactions.len() {
+            return Err("Block transaction count doesn't match its transactions");
         }
 
-        if !self.is_block_hash_valid(&block.hash) {
-            return Err("Block hash doesn't meet difficulty requirements");
+        if !self.verify_block_merkle_root(block) {
+            return Err("Block has invalid merkle root");
         }
 
-        self.validate_transactions(block)?;
+        let state = self.validate_transactions(block, state)?;
+        if block.state_root != state.get_root() {
+            return Err("Block has invalid state root");
+        }
 
-        Ok(())
+        Ok(state)
     }
 
-    fn validate_transactions(&self, block: &Block) -> Result<(), &'static str> {
-        if block.index == 0 || block.transactions.is_empty() {
-            return Ok(());
+    fn validate_transactions(&self, block: &Block, state: &ChainState) -> Result<ChainState, &'static str> {
+        let mut state = state.clone();
+        if block.index == 0 {
+            return Ok(state);
         }
 
+        self.validate_coinbase(block)?;
+
         let mut tx_hashes = HashSet::new();
         for tx in &block.transactions {
             if !tx_hashes.insert(tx.tx_hash.clone()) {
                 return Err("Duplicate transaction in block");
             }
 
-            if !tx.verify() {
-                return Err("Block contains invalid transaction signature");
+            if !tx.verify(self.get_chain_id()) {
+                return Err("Block contains invalid transaction");
             }
 
-            if let Some(amount) = tx.data.amount {
-                let sender_balance = self.balances.get(&tx.data.sender).unwrap_or(&0);
-                let total_cost = amount + tx.data.fee;
-
-                if *sender_balance < total_cost {
-                    return Err("Insufficient balance for transaction");
-                }
-            }
+            state.apply_transaction(tx)?;
         }
 
-        Ok(())
+        Ok(state)
     }
 
-    fn process_block_transactions(&mut self, block: &Block) -> Result<(), &'static str> {
-        if block.index == 0 || block.transactions.is_empty() {
-            return Ok(());
+    fn validate_coinbase(&self, block: &Block) -> Result<(), &'static str> {
+        let coinbase = match block.transactions.first() {
+            Some(tx) if tx.is_coinbase() => tx,
+            _ => return Err("Block must start with a coinbase transaction"),
+        };
+
+        if block.transactions.iter().skip(1).any(|tx| tx.is_coinbase()) {
+            return Err("Block contains more than one coinbase transaction");
         }
 
-        for tx in &block.transactions {
-            if !tx.verify() {
-                return Err("Block contains invalid transaction");
-            }
+        if coinbase.data.nonce != block.index as u64 {
+            return Err("Coinbase height doesn't match block index");
+        }
 
-            match tx.data.tx_type {
-                TransactionType::Transfer => {
-                    if let Some(amount) = tx.data.amount {
-                        let sender_balance = self.balances.entry(tx.data.sender.clone())
-                            .or_insert(0);
-                        *sender_balance = sender_balance.saturating_sub(amount + tx.data.fee);
-
-                        if let Some(receiver) = &tx.data.receiver {
-                            let receiver_balance = self.balances.entry(receiver.clone())
-                                .or_insert(0);
-                            *receiver_balance += amount;
-                        }
-                    }
-                }
-                TransactionType::Data => {
-                    let sender_balance = self.balances.entry(tx.data.sender.clone())
-                        .or_insert(0);
-                    *sender_balance = sender_balance.saturating_sub(tx.data.fee);
-                }
-            }
+        let fees = Self::total_fees(&block.transactions[1..]).ok_or("Transaction fees overflow")?;
+        let max_reward = self.block_subsidy.checked_add(fees).ok_or("Block reward overflow")?;
+        if coinbase.data.amount.unwrap_or(0) > max_reward {
+            return Err("Coinbase reward exceeds subsidy plus fees");
         }
 
         Ok(())
     }
 
+    fn total_fees(transactions: &[Transaction]) -> Option<u64> {
+        transactions
+            .iter()
+            .try_fold(0u64, |total, tx| total.checked_add(tx.data.fee))
+    }
+
     pub fn get_last_block(&self) -> Option<&Block> {
         self.blocks.last()
     }
 
+    pub fn get_block_by_hash(&self, hash: &[u8]) -> Option<&Block> {
+        let entry = self.tree.get(hash)?;
+        self.blocks
+            .get(entry.header.index as usize)
+            .filter(|block| block.hash == hash)
+    }
+
+    pub fn contains_block(&self, hash: &[u8]) -> bool {
+        self.tree.contains(hash)
+    }
+
+    pub fn is_orphan(&self, hash: &[u8]) -> bool {
+        self.tree.is_orphan(hash)
+    }
+
+    fn check_checkpoint(&self, block: &Block) -> Result<(), &'static str> {
+        match self.checkpoints.get(&block.index) {
+            Some(hash) if *hash != block.hash => Err("Block doesn't match checkpoint"),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn get_finalized_height(&self) -> usize {
+        let tip = self.blocks.len().saturating_sub(1);
+        let checkpoint = self.checkpoints
+            .keys()
+            .map(|height| *height as usize)
+            .filter(|height| *height <= tip)
+            .max()
+            .unwrap_or(0);
+
+        tip.saturating_sub(FINALITY_DEPTH).max(checkpoint)
+    }
+
+    fn is_main_chain(&self, header: &BlockHeader) -> bool {
+        self.blocks.get(header.index as usize).is_some_and(|block| block.hash == header.hash)
+    }
+
+    pub fn get_cumulative_work(&self) -> U256 {
+        self.get_last_block().map_or(U256::ZERO, |block| self.tree.get_work(&block.hash))
+    }
+
     pub fn get_balance(&self, address: &PublicKey) -> u64 {
-        *self.balances.get(address).unwrap_or(&0)
+        self.state.get_balance(address)
     }
 
     pub fn get_block_height(&self) -> usize {
@@ -195,47 +352,63 @@ impl Blockchain {
     }
 
     pub fn get_next_nonce(&self, sender: &PublicKey) -> u64 {
-        let mut confirmed_nonce = 0;
-        for block in &self.blocks {
-            for tx in &block.transactions {
-                if tx.data.sender == *sender && tx.data.nonce > confirmed_nonce {
-                    confirmed_nonce = tx.data.nonce;
-                }
-            }
-        }
-        confirmed_nonce + 1
+        self.state.get_next_nonce(sender)
     }
 
-    fn is_block_hash_valid(&self, hash: &[u8]) -> bool {
-        let hash_string = hex::encode(hash);
-        let target = "0".repeat(self.difficulty);
-        hash_string.starts_with(&target)
+    fn get_state_at(&self, height: usize) -> Option<ChainState> {
+        if height >= self.blocks.len() {
+            return None;
+        }
+        if height == self.blocks.len() - 1 {
+            return Some(self.state.clone());
+        }
+
+        let (&snapshot_height, snapshot) = self.snapshots.range(..=height).next_back()?;
+        let mut state = snapshot.clone();
+        for block in &self.blocks[snapshot_height + 1..=height] {
+            state.apply_block(block).ok()?;
+        }
+        Some(state)
     }
 
-    pub fn is_chain_valid(&self, chain: Option<&Vec<Block>>) -> bool {
-        let chain_to_validate = chain.unwrap_or(&self.blocks);
+    pub fn get_account_proof(&self, address: &PublicKey, height: usize) -> Option<AccountProof> {
+        if height >= self.blocks.len() {
+            return None;
+        }
+        if height == self.blocks.len() - 1 {
+            return self.state.get_proof(address);
+        }
 
-        for i in 0..chain_to_validate.len() {
-            let current_block = &chain_to_validate[i];
+        self.get_state_at(height)?.get_proof(address)
+    }
 
-            if current_block.hash != current_block.hash() {
-                return false;
-            }
+    pub fn get_next_bits(&self) -> u32 {
+        next_bits(&self.blocks)
+    }
 
-            if !self.verify_block_merkle_root(current_block) {
-                return false;
-            }
+    fn meets_orphan_floor(&self, block: &Block) -> bool {
+        match (U256::from_compact(block.bits), U256::from_compact(self.get_next_bits())) {
+            (Some(target), Some(floor)) => target <= floor.saturating_mul_u64(MAX_RETARGET_FACTOR),
+            _ => false,
+        }
+    }
 
-            if i > 0 {
-                let prev_block = &chain_to_validate[i - 1];
+    fn is_block_hash_valid(&self, block: &Block) -> bool {
+        block.hash == block.hash() && hash_meets_target(&block.hash, block.bits)
+    }
 
-                if current_block.prev_hash != prev_block.hash {
-                    return false;
-                }
+    pub fn is_chain_valid(&self, chain: Option<&Vec<Block>>) -> bool {
+        let chain_to_validate = chain.unwrap_or(&self.blocks);
 
-                if !self.is_block_hash_valid(&current_block.hash()) {
-                    return false;
-                }
+        if chain_to_validate.first().is_none_or(|block| block.hash != self.get_genesis_hash()) {
+            return false;
+        }
+
+        let mut state = self.genesis_state();
+        for i in 1..chain_to_validate.len() {
+            match self.validate_block(&chain_to_validate[i], &chain_to_validate[..i], &state) {
+                Ok(next_state) => state = next_state,
+                Err(_) => return false,
             }
         }
         true
@@ -251,121 +424,215 @@ impl Blockchain {
     }
 
     pub fn receive_block(&mut self, block: Block) -> Result<(), &'static str> {
-        if !self.is_block_hash_valid(&block.hash) {
+        if !self.is_block_hash_valid(&block) {
             return Err("Block hash doesn't meet difficulty requirements");
         }
 
         let current_time = now();
-        if block.timestamp > current_time + 7_200_000 {
+        if block.timestamp > current_time + MAX_FUTURE_BLOCK_TIME {
             return Err("Block timestamp is too far in the future");
         }
 
-        if let Some(last_block) = self.blocks.last() {
-            if block.prev_hash == last_block.hash && block.index == last_block.index + 1 {
-                return self.add_block(block);
+        if self.tree.contains(&block.hash) || self.tree.is_orphan(&block.hash) {
+            return Err("Block is already known");
+        }
+
+        if !self.tree.contains(&block.prev_hash) {
+            if !self.meets_orphan_floor(&block) {
+                return Err("Orphan block difficulty is below the current floor");
             }
+            self.tree.add_orphan(block);
+            return Err("Block parent is unknown");
         }
 
-        for (i, existing_block) in self.blocks.iter().enumerate() {
-            if block.prev_hash == existing_block.hash {
-                if (self.blocks.len() - i) > MAX_FORK_DEPTH {
-                    return Err("Fork depth exceeded");
-                }
+        let hash = block.hash.clone();
+        self.accept_block(block)?;
 
-                let mut fork_chain = self.blocks[0..=i].to_vec();
-                if !self.validate_fork_chain(&fork_chain, &block) {
-                    return Err("Invalid fork chain");
+        let mut parents = vec![hash];
+        while let Some(parent) = parents.pop() {
+            for orphan in self.tree.take_orphans(&parent) {
+                let orphan_hash = orphan.hash.clone();
+                if self.accept_block(orphan).is_ok() {
+                    parents.push(orphan_hash);
                 }
-
-                fork_chain.push(block.clone());
-                self.forks.insert(existing_block.hash.clone(), fork_chain);
-                self.resolve_forks();
-                return Ok(());
             }
         }
 
-        Err("Block doesn't fit in any chain")
+        self.activate_best_chain()
     }
 
-    fn validate_fork_chain(&self, fork_chain: &Vec<Block>, new_block: &Block) -> bool {
-        for i in 1..fork_chain.len() {
-            let current = &fork_chain[i];
-            let prev = &fork_chain[i - 1];
+    fn accept_block(&mut self, block: Block) -> Result<(), &'static str> {
+        if block.index as usize <= self.get_finalized_height() {
+            return Err("Block conflicts with a finalized block");
+        }
+        self.check_checkpoint(&block)?;
+
+        let ancestors = self.tree.get_ancestors(&block.prev_hash, RETARGET_INTERVAL as usize);
+        validate_header(&block.get_header(), &ancestors)?;
+
+        if !self.verify_block_merkle_root(&block) {
+            return Err("Block has invalid merkle root");
+        }
+
+        self.tree.insert_side_block(block);
+        Ok(())
+    }
 
-            if current.prev_hash != prev.hash {
-                return false;
+    fn activate_best_chain(&mut self) -> Result<(), &'static str> {
+        let mut result = Ok(());
+
+        while let Some(best) = self.tree.get_best_side_tip() {
+            if best.cumulative_work <= self.get_cumulative_work() {
+                break;
             }
 
-            if !self.is_block_hash_valid(&current.hash()) {
-                return false;
+            let hash = best.header.hash.clone();
+            if let Err(e) = self.switch_to(&hash) {
+                result = Err(e);
             }
         }
 
-        let last_fork = fork_chain.last().unwrap();
-        new_block.prev_hash == last_fork.hash &&
-            new_block.index == last_fork.index + 1 &&
-            self.is_block_hash_valid(&new_block.hash())
+        result
     }
 
-    pub fn resolve_forks(&mut self) {
-        let main_chain_length = self.blocks.len();
-        let mut forks_to_remove = Vec::new();
-        let fork_keys: Vec<_> = self.forks.keys().cloned().collect();
-
-        for fork_key in fork_keys {
-            let fork_chain_cloned = self.forks.get(&fork_key).cloned();
+    fn switch_to(&mut self, tip: &[u8]) -> Result<(), &'static str> {
+        let Some((fork_height, branch)) = self.tree.get_branch(tip, |header| self.is_main_chain(header)) else {
+            self.tree.remove_branch(tip);
+            return Err("Block branch doesn't connect to the main chain");
+        };
 
-            if let Some(fork_chain) = fork_chain_cloned {
-                if fork_chain.len() > main_chain_length + MAX_FORK_DEPTH {
-                    forks_to_remove.push(fork_key.clone());
-                    continue;
-                }
+        if fork_height < self.get_finalized_height() {
+            self.tree.remove_branch(tip);
+            return Err("Reorganization would revert finalized blocks");
+        }
 
-                if self.is_chain_valid(Some(&fork_chain)) && fork_chain.len() > main_chain_length {
-                    if self.get_cumulative_work(&fork_chain) > self.get_cumulative_work(&self.blocks) {
-                        self.revert_to_fork_state(&fork_chain);
-                        self.blocks = fork_chain.clone();
-                        forks_to_remove.extend(self.forks.keys().cloned());
-                        break;
-                    }
+        if fork_height + 1 == self.blocks.len() {
+            for block in branch {
+                let hash = block.hash.clone();
+                if let Err(e) = self.add_block(block) {
+                    self.tree.remove_branch(&hash);
+                    return Err(e);
                 }
+            }
+            return Ok(());
+        }
 
-                if !self.is_chain_valid(Some(&fork_chain)) {
-                    forks_to_remove.push(fork_key.clone());
+        let Some(mut state) = self.get_state_at(fork_height) else {
+            self.tree.remove_branch(branch.first().map_or(tip, |block| &block.hash));
+            return Err("Failed to rebuild chain state");
+        };
+        let start = (fork_height + 1).saturating_sub(RETARGET_INTERVAL as usize);
+        let mut chain = self.blocks[start..=fork_height].to_vec();
+
+        let mut snapshots = Vec::new();
+        for block in &branch {
+            match self.validate_block(block, &chain, &state) {
+                Ok(next_state) => state = next_state,
+                Err(e) => {
+                    self.tree.remove_branch(&block.hash);
+                    return Err(e);
                 }
             }
+            chain.push(block.clone());
+            if (block.index as usize).is_multiple_of(STATE_SNAPSHOT_INTERVAL) {
+                snapshots.push((block.index as usize, state.clone()));
+            }
         }
 
-        for key in forks_to_remove {
-            self.forks.remove(&key);
+        let disconnected = self.blocks.split_off(fork_height + 1);
+        self.snapshots.split_off(&(fork_height + 1));
+        self.snapshots.extend(snapshots);
+        self.prune_snapshots();
+        for block in disconnected.iter().rev() {
+            self.index.disconnect_block(block);
+        }
+        for block in &branch {
+            self.tree.connect_block(block);
+            self.index.connect_block(block);
+        }
+        for block in &disconnected {
+            self.tree.disconnect_block(block.clone());
         }
+
+        self.blocks.extend(branch);
+        self.state = state;
+        self.notify_listeners(&disconnected, &self.blocks[fork_height + 1..]);
+        self.persist_from(fork_height + 1)
+    }
+
+    pub fn get_block_headers(&self) -> Vec<BlockHeader> {
+        self.blocks.iter().map(|block| block.get_header()).collect()
     }
 
-    fn get_cumulative_work(&self, chain: &Vec<Block>) -> u128 {
-        chain.iter().map(|block| {
-            let leading_zeros = block.hash.iter()
-                .take_while(|&&byte| byte == 0)
-                .count() * 8 +
-                block.hash.iter()
-                    .find(|&&byte| byte != 0)
-                    .map_or(0, |&byte| byte.leading_zeros() as usize);
-            2u128.pow(leading_zeros as u32)
-        }).sum()
+    pub fn get_block_header(&self, index: usize) -> Option<BlockHeader> {
+        self.blocks.get(index).map(|block| block.get_header())
     }
 
-    fn revert_to_fork_state(&mut self, fork_chain: &Vec<Block>) {
-        self.balances.clear();
-        for block in fork_chain {
-            let _ = self.process_block_transactions(block);
+    pub fn get_locator(&self) -> Vec<BHash> {
+        let mut locator = Vec::new();
+        let mut index = self.blocks.len().saturating_sub(1);
+        let mut step = 1;
+
+        while index > 0 {
+            locator.push(self.blocks[index].hash.clone());
+            if locator.len() >= 10 {
+                step *= 2;
+            }
+            index = index.saturating_sub(step);
         }
+
+        locator.push(self.get_genesis_hash());
+        locator
     }
 
-    pub fn get_block_headers(&self) -> Vec<crate::blockchain::block::BlockHeader> {
-        self.blocks.iter().map(|block| block.get_header()).collect()
+    pub fn find_fork_point(&self, locator: &[BHash]) -> Option<usize> {
+        locator
+            .iter()
+            .find_map(|hash| self.blocks.iter().position(|block| block.hash == *hash))
     }
 
-    pub fn get_block_header(&self, index: usize) -> Option<crate::blockchain::block::BlockHeader> {
-        self.blocks.get(index).map(|block| block.get_header())
+    pub fn get_headers_range(&self, start: usize, count: usize) -> Vec<BlockHeader> {
+        self.blocks.iter().skip(start).take(count).map(|block| block.get_header()).collect()
+    }
+
+    pub fn get_blocks_range(&self, start: usize, count: usize) -> Vec<Block> {
+        self.blocks.iter().skip(start).take(count).cloned().collect()
+    }
+
+    pub fn get_transaction_location(&self, tx_hash: &[u8]) -> Option<TxLocation> {
+        self.index.get_location(tx_hash)
+    }
+
+    fn get_transaction_at(&self, location: &TxLocation) -> Option<&Transaction> {
+        self.blocks.get(location.height)?.transactions.get(location.position)
+    }
+
+    pub fn get_transaction(&self, tx_hash: &[u8]) -> Option<&Transaction> {
+        self.get_transaction_at(&self.get_transaction_location(tx_hash)?)
+    }
+
+    pub fn get_address_transactions(&self, address: &[u8]) -> Vec<&Transaction> {
+        self.index
+            .get_address_locations(address)
+            .iter()
+            .filter_map(|location| self.get_transaction_at(location))
+            .collect()
+    }
+
+    pub fn get_auction_transactions(&self, auction_id: &str) -> Vec<&Transaction> {
+        self.index
+            .get_auction_locations(auction_id)
+            .iter()
+            .filter_map(|location| self.get_transaction_at(location))
+            .collect()
+    }
+
+    pub fn get_all_auction_transactions(&self) -> Vec<&Transaction> {
+        self.index
+            .get_all_auction_locations()
+            .iter()
+            .filter_map(|location| self.get_transaction_at(location))
+            .collect()
     }
 
     pub fn get_transaction_proof(&self, block_index: usize, tx_hash: &[u8]) -> Option<MerkleProof> {
@@ -385,28 +652,72 @@ impl Blockchain {
     }
 }
 
+pub fn validate_header<H: ChainHeader + Hashable>(header: &H, chain: &[H]) -> Result<(), &'static str> {
+    let parent = chain.last()
+        .ok_or("No blocks in chain")?;
+
+    if header.prev_hash() != parent.block_hash() {
+        return Err("Block has invalid previous hash");
+    }
+
+    if header.index() != parent.index() + 1 {
+        return Err("Block has invalid index");
+    }
+
+    let time_diff = header.timestamp().saturating_sub(parent.timestamp());
+    if time_diff < MIN_BLOCK_TIME {
+        return Err("Block time is too short");
+    }
+    if parent.index() > 0 && time_diff > MAX_BLOCK_TIME {
+        return Err("Block time is too long");
+    }
+
+    if header.bits() != next_bits(chain) {
+        return Err("Block has invalid difficulty");
+    }
+
+    if header.block_hash() != header.hash() || !hash_meets_target(header.block_hash(), header.bits()) {
+        return Err("Block hash doesn't meet difficulty requirements");
+    }
+
+    Ok(())
+}
+
 pub struct LightClient {
-    headers: Vec<block::BlockHeader>,
+    headers: Vec<BlockHeader>,
+}
+
+impl Default for LightClient {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl LightClient {
     pub fn new() -> Self {
-        LightClient {
-            headers: Vec::new(),
-        }
+        Self::from_genesis(GenesisConfig::shared())
     }
 
-    pub fn add_header(&mut self, header: block::BlockHeader) -> Result<(), &'static str> {
-        if let Some(last_header) = self.headers.last() {
-            if header.prev_hash != last_header.hash {
-                return Err("Invalid header chain");
-            }
+    pub fn from_genesis(genesis: &GenesisConfig) -> Self {
+        Self::from_headers(vec![Block::genesis(genesis).get_header()])
+    }
+
+    pub fn from_headers(headers: Vec<BlockHeader>) -> Self {
+        LightClient {
+            headers,
         }
+    }
 
+    pub fn add_header(&mut self, header: BlockHeader) -> Result<(), &'static str> {
+        validate_header(&header, &self.headers)?;
         self.headers.push(header);
         Ok(())
     }
 
+    pub fn get_header(&self, index: usize) -> Option<&BlockHeader> {
+        self.headers.get(index)
+    }
+
     pub fn verify_transaction(&self, tx_hash: &[u8], proof: &MerkleProof, block_index: usize) -> bool {
         if let Some(header) = self.headers.get(block_index) {
             MerkleTree::verify_proof(&header.merkle_root, tx_hash, proof)
@@ -415,7 +726,21 @@ impl LightClient {
         }
     }
 
+    pub fn verify_balance(&self, address: &PublicKey, proof: &AccountProof, height: usize) -> bool {
+        if let Some(header) = self.headers.get(height) {
+            proof.account.address == *address && proof.verify(&header.state_root)
+        } else {
+            false
+        }
+    }
+
     pub fn get_height(&self) -> usize {
         self.headers.len()
     }
+
+    pub fn get_cumulative_work(&self) -> U256 {
+        self.headers
+            .iter()
+            .fold(U256::ZERO, |work, header| work.saturating_add(&work_from_bits(header.bits)))
+    }
 }
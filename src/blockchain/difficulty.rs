@@ -0,0 +1,37 @@
+use crate::blockchain::block::ChainHeader;
+use crate::blockchain::target::U256;
+use crate::constants::{BLOCK_INTERVAL, MAX_RETARGET_FACTOR, POW_LIMIT_BITS, RETARGET_INTERVAL};
+
+pub fn next_bits<H: ChainHeader>(chain: &[H]) -> u32 {
+    let Some(parent) = chain.last() else {
+        return POW_LIMIT_BITS;
+    };
+
+    let height = parent.index() + 1;
+    if height % RETARGET_INTERVAL != 0 || chain.len() < RETARGET_INTERVAL as usize {
+        return parent.bits();
+    }
+
+    let first = &chain[chain.len() - RETARGET_INTERVAL as usize];
+    if first.index() == 0 {
+        return parent.bits();
+    }
+
+    let Some(target) = U256::from_compact(parent.bits()) else {
+        return POW_LIMIT_BITS;
+    };
+
+    let expected_timespan = BLOCK_INTERVAL.as_millis() as u64 * (RETARGET_INTERVAL as u64 - 1);
+    let actual_timespan = (parent.timestamp().saturating_sub(first.timestamp()) as u64).clamp(
+        expected_timespan / MAX_RETARGET_FACTOR,
+        expected_timespan * MAX_RETARGET_FACTOR,
+    );
+
+    let pow_limit = U256::from_compact(POW_LIMIT_BITS).unwrap();
+    let next_target = target
+        .saturating_mul_u64(actual_timespan)
+        .div_u64(expected_timespan)
+        .min(pow_limit);
+
+    next_target.to_compact()
+}
@@ -0,0 +1,299 @@
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub const ENCODING_VERSION: u8 = 2;
+
+pub trait Encode {
+    fn encode(&self, encoder: &mut Encoder);
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        encoder.write_u8(ENCODING_VERSION);
+        self.encode(&mut encoder);
+        encoder.into_bytes()
+    }
+}
+
+pub trait Decode: Sized {
+    fn decode(decoder: &mut Decoder) -> Result<Self, &'static str>;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut decoder = Decoder::new(bytes);
+        if decoder.read_u8()? != ENCODING_VERSION {
+            return Err("Unsupported encoding version");
+        }
+
+        let value = Self::decode(&mut decoder)?;
+        if !decoder.is_empty() {
+            return Err("Trailing bytes after encoded value");
+        }
+        Ok(value)
+    }
+}
+
+#[derive(Default)]
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Encoder {
+            bytes: Vec::new(),
+        }
+    }
+
+    pub fn write_u8(&mut self, n: u8) {
+        self.bytes.push(n);
+    }
+
+    pub fn write_u32(&mut self, n: u32) {
+        self.bytes.extend(n.to_be_bytes());
+    }
+
+    pub fn write_u64(&mut self, n: u64) {
+        self.bytes.extend(n.to_be_bytes());
+    }
+
+    pub fn write_u128(&mut self, n: u128) {
+        self.bytes.extend(n.to_be_bytes());
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_u32(bytes.len() as u32);
+        self.bytes.extend(bytes);
+    }
+
+    pub fn write_str(&mut self, s: &str) {
+        self.write_bytes(s.as_bytes());
+    }
+
+    pub fn write_option<T>(&mut self, value: &Option<T>, write: impl FnOnce(&mut Self, &T)) {
+        match value {
+            Some(value) => {
+                self.write_u8(1);
+                write(self, value);
+            }
+            None => self.write_u8(0),
+        }
+    }
+
+    pub fn write_seq<T: Encode>(&mut self, values: &[T]) {
+        self.write_u32(values.len() as u32);
+        for value in values {
+            value.encode(self);
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Decoder {
+            bytes,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], &'static str> {
+        if len > self.bytes.len() {
+            return Err("Unexpected end of encoded data");
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, &'static str> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, &'static str> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, &'static str> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn read_u128(&mut self) -> Result<u128, &'static str> {
+        Ok(u128::from_be_bytes(self.take(16)?.try_into().unwrap()))
+    }
+
+    pub fn read_bytes(&mut self) -> Result<Vec<u8>, &'static str> {
+        let len = self.read_u32()? as usize;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    pub fn read_string(&mut self) -> Result<String, &'static str> {
+        String::from_utf8(self.read_bytes()?).map_err(|_| "Encoded string is not valid UTF-8")
+    }
+
+    pub fn read_option<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T, &'static str>,
+    ) -> Result<Option<T>, &'static str> {
+        match self.read_u8()? {
+            0 => Ok(None),
+            1 => read(self).map(Some),
+            _ => Err("Invalid option tag"),
+        }
+    }
+
+    pub fn read_seq<T: Decode>(&mut self) -> Result<Vec<T>, &'static str> {
+        let len = self.read_u32()? as usize;
+        let mut values = Vec::with_capacity(len.min(self.bytes.len()));
+        for _ in 0..len {
+            values.push(T::decode(self)?);
+        }
+        Ok(values)
+    }
+}
+
+pub fn serialize<T: Encode, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&hex::encode(value.to_bytes()))
+    } else {
+        serializer.serialize_bytes(&value.to_bytes())
+    }
+}
+
+pub fn deserialize<'de, T: Decode, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    let bytes = if deserializer.is_human_readable() {
+        hex::decode(String::deserialize(deserializer)?).map_err(DeError::custom)?
+    } else {
+        Vec::<u8>::deserialize(deserializer)?
+    };
+    T::from_bytes(&bytes).map_err(DeError::custom)
+}
+
+macro_rules! canonical_serde {
+    ($type:ty) => {
+        impl serde::Serialize for $type {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $crate::blockchain::encoding::serialize(self, serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $type {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                $crate::blockchain::encoding::deserialize(deserializer)
+            }
+        }
+    };
+}
+
+pub(crate) use canonical_serde;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::block::{Block, BlockHeader};
+    use crate::blockchain::transaction::{Transaction, TransactionType};
+    use crate::blockchain::Hashable;
+
+    const CHAIN_ID: &str = "ledger-testnet";
+
+    fn transfer() -> Transaction {
+        let keypair = Transaction::generate_keypair();
+        Transaction::create_transfer(&keypair, CHAIN_ID, vec![7; 32], 10, 1, 2).unwrap()
+    }
+
+    fn data_tx() -> Transaction {
+        let keypair = Transaction::generate_keypair();
+        Transaction::create_data_tx(&keypair, CHAIN_ID, "data".to_string(), 1, 0).unwrap()
+    }
+
+    fn block() -> Block {
+        Block::new(1, 1_735_689_600_000, vec![1; 32], 42, 0x1f00_ffff, vec![transfer(), data_tx()])
+    }
+
+    #[test]
+    fn transaction_round_trips() {
+        for tx in [transfer(), data_tx()] {
+            let bytes = tx.to_bytes();
+            let decoded = Transaction::from_bytes(&bytes).unwrap();
+
+            assert_eq!(decoded.to_bytes(), bytes);
+            assert_eq!(decoded.tx_hash, tx.tx_hash);
+            assert_eq!(decoded.signature, tx.signature);
+            assert!(decoded.verify(CHAIN_ID));
+        }
+    }
+
+    #[test]
+    fn block_round_trips() {
+        let block = block();
+        let bytes = block.to_bytes();
+        let decoded = Block::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(decoded.merkle_root, block.merkle_root);
+        assert_eq!(decoded.transactions.len(), 2);
+    }
+
+    #[test]
+    fn header_round_trips() {
+        let header = block().get_header();
+        let bytes = header.to_bytes();
+        let decoded = BlockHeader::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(decoded.prev_hash, header.prev_hash);
+        assert_eq!(decoded.nonce, header.nonce);
+    }
+
+    #[test]
+    fn block_hash_ignores_hash_field() {
+        let mut block = block();
+        let hash = block.hash();
+        block.hash = hash.clone();
+
+        assert_eq!(block.hash(), hash);
+        assert_eq!(block.get_header().hash(), hash);
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let bytes = block().to_bytes();
+        for len in 0..bytes.len() {
+            assert_eq!(Block::from_bytes(&bytes[..len]).unwrap_err(), "Unexpected end of encoded data");
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        let mut bytes = transfer().to_bytes();
+        bytes.push(0);
+        assert_eq!(Transaction::from_bytes(&bytes).unwrap_err(), "Trailing bytes after encoded value");
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let mut bytes = block().to_bytes();
+        bytes[0] = ENCODING_VERSION + 1;
+        assert_eq!(Block::from_bytes(&bytes).unwrap_err(), "Unsupported encoding version");
+    }
+
+    #[test]
+    fn rejects_unknown_transaction_type() {
+        let mut bytes = data_tx().to_bytes();
+        let offset = 1 + 4 + CHAIN_ID.len() + 4 + 32 + 1 + 16;
+        assert_eq!(bytes[offset], 1);
+
+        bytes[offset] = 3;
+        assert_eq!(Transaction::from_bytes(&bytes).unwrap_err(), "Unknown transaction type");
+        assert_eq!(TransactionType::from_bytes(&[ENCODING_VERSION, 3]).unwrap_err(), "Unknown transaction type");
+    }
+}
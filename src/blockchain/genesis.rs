@@ -0,0 +1,172 @@
+use super::*;
+use crate::blockchain::lib::{u128_to_bytes, u32_to_bytes, u64_to_bytes, BHash};
+use crate::blockchain::transaction::PublicKey;
+use crate::blockchain::target::U256;
+use crate::constants::{BLOCK_SUBSIDY, CHECKPOINTS, GENESIS_BITS, GENESIS_FILE, GENESIS_TIMESTAMP, POW_LIMIT_BITS};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+static GENESIS: OnceLock<GenesisConfig> = OnceLock::new();
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GenesisAllocation {
+    pub address: String,
+    pub balance: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub height: u32,
+    pub hash: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GenesisConfig {
+    pub chain_id: String,
+    pub timestamp: u128,
+    pub bits: u32,
+    pub block_subsidy: u64,
+    pub allocations: Vec<GenesisAllocation>,
+    #[serde(default)]
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+impl Default for GenesisConfig {
+    fn default() -> Self {
+        GenesisConfig {
+            chain_id: "ledger-testnet".to_string(),
+            timestamp: GENESIS_TIMESTAMP,
+            bits: GENESIS_BITS,
+            block_subsidy: BLOCK_SUBSIDY,
+            allocations: Vec::new(),
+            checkpoints: Vec::new(),
+        }
+    }
+}
+
+impl GenesisConfig {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = fs::read_to_string(path)?;
+        let config: GenesisConfig = serde_json::from_str(&contents)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn load_or_default() -> Result<Self, Box<dyn std::error::Error>> {
+        match Self::load(GENESIS_FILE) {
+            Err(e) if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::NotFound) => {
+                Ok(Self::default())
+            }
+            result => result,
+        }
+    }
+
+    pub fn init() -> Result<&'static Self, Box<dyn std::error::Error>> {
+        if let Some(config) = GENESIS.get() {
+            return Ok(config);
+        }
+
+        let config = Self::load_or_default()?;
+        Ok(GENESIS.get_or_init(|| config))
+    }
+
+    pub fn shared() -> &'static Self {
+        GENESIS.get_or_init(|| {
+            Self::load_or_default().unwrap_or_else(|e| {
+                eprintln!("Using the default genesis, {} is unusable: {}", GENESIS_FILE, e);
+                Self::default()
+            })
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.chain_id.is_empty() || self.chain_id.len() > 64 {
+            return Err("Chain ID must have between 1 and 64 characters");
+        }
+
+        let target = U256::from_compact(self.bits).ok_or("Genesis bits are not a valid target")?;
+        if target > U256::from_compact(POW_LIMIT_BITS).unwrap() {
+            return Err("Genesis target is above the proof of work limit");
+        }
+
+        let balances = self.get_balances()?;
+        if balances.len() != self.allocations.len() {
+            return Err("Duplicate address in genesis allocations");
+        }
+
+        balances
+            .values()
+            .try_fold(0u64, |total, balance| total.checked_add(*balance))
+            .ok_or("Genesis allocations overflow")?;
+
+        self.get_checkpoints()?;
+
+        Ok(())
+    }
+
+    pub fn get_balances(&self) -> Result<HashMap<PublicKey, u64>, &'static str> {
+        let mut balances = HashMap::new();
+
+        for allocation in &self.allocations {
+            let address = hex::decode(&allocation.address)
+                .map_err(|_| "Genesis allocation address must be hex encoded")?;
+            if address.len() != 32 {
+                return Err("Genesis allocation address must be a 32 byte public key");
+            }
+            balances.insert(address, allocation.balance);
+        }
+
+        Ok(balances)
+    }
+
+    pub fn get_checkpoints(&self) -> Result<HashMap<u32, BHash>, &'static str> {
+        let hard_coded = CHECKPOINTS
+            .iter()
+            .filter(|(chain_id, _, _)| *chain_id == self.chain_id)
+            .map(|(_, height, hash)| (*height, *hash));
+        let configured = self
+            .checkpoints
+            .iter()
+            .map(|checkpoint| (checkpoint.height, checkpoint.hash.as_str()));
+
+        let mut checkpoints = HashMap::new();
+        for (height, hash) in hard_coded.chain(configured) {
+            let hash = hex::decode(hash).map_err(|_| "Checkpoint hash must be hex encoded")?;
+            if hash.len() != 32 {
+                return Err("Checkpoint hash must have 32 bytes");
+            }
+            if checkpoints.insert(height, hash.clone()).is_some_and(|existing| existing != hash) {
+                return Err("Conflicting checkpoints for the same height");
+            }
+        }
+
+        Ok(checkpoints)
+    }
+}
+
+impl Hashable for GenesisConfig {
+    fn bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+
+        bytes.extend(&u32_to_bytes(&(self.chain_id.len() as u32)));
+        bytes.extend(self.chain_id.as_bytes());
+        bytes.extend(&u128_to_bytes(&self.timestamp));
+        bytes.extend(&u32_to_bytes(&self.bits));
+        bytes.extend(&u64_to_bytes(&self.block_subsidy));
+
+        let mut allocations: Vec<_> = self.get_balances().unwrap_or_default().into_iter().collect();
+        allocations.sort();
+
+        bytes.extend(&u32_to_bytes(&(allocations.len() as u32)));
+        for (address, balance) in allocations {
+            bytes.extend(&address);
+            bytes.extend(&u64_to_bytes(&balance));
+        }
+
+        bytes
+    }
+}
@@ -0,0 +1,103 @@
+use crate::blockchain::block::Block;
+use crate::blockchain::transaction::{PublicKey, Transaction, TxHash};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TxLocation {
+    pub height: usize,
+    pub position: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct ChainIndex {
+    transactions: HashMap<TxHash, TxLocation>,
+    addresses: HashMap<PublicKey, Vec<TxLocation>>,
+    auctions: HashMap<String, Vec<TxLocation>>,
+}
+
+impl ChainIndex {
+    pub fn from_chain(chain: &[Block]) -> Self {
+        let mut index = ChainIndex::default();
+        for block in chain {
+            index.connect_block(block);
+        }
+        index
+    }
+
+    pub fn connect_block(&mut self, block: &Block) {
+        let height = block.index as usize;
+
+        for (position, tx) in block.transactions.iter().enumerate() {
+            let location = TxLocation {
+                height,
+                position,
+            };
+
+            self.transactions.insert(tx.tx_hash.clone(), location);
+            for address in Self::get_addresses(tx) {
+                self.addresses.entry(address.clone()).or_default().push(location);
+            }
+            if let Some(auction_id) = tx.get_auction_id() {
+                self.auctions.entry(auction_id).or_default().push(location);
+            }
+        }
+    }
+
+    pub fn disconnect_block(&mut self, block: &Block) {
+        let height = block.index as usize;
+
+        for tx in &block.transactions {
+            if self.transactions.get(&tx.tx_hash).is_some_and(|location| location.height == height) {
+                self.transactions.remove(&tx.tx_hash);
+            }
+            for address in Self::get_addresses(tx) {
+                Self::remove_from_height(&mut self.addresses, address, height);
+            }
+            if let Some(auction_id) = tx.get_auction_id() {
+                Self::remove_from_height(&mut self.auctions, &auction_id, height);
+            }
+        }
+    }
+
+    fn remove_from_height<K: Hash + Eq>(index: &mut HashMap<K, Vec<TxLocation>>, key: &K, height: usize) {
+        let Some(locations) = index.get_mut(key) else {
+            return;
+        };
+
+        while locations.last().is_some_and(|location| location.height >= height) {
+            locations.pop();
+        }
+        if locations.is_empty() {
+            index.remove(key);
+        }
+    }
+
+    fn get_addresses(tx: &Transaction) -> Vec<&PublicKey> {
+        let mut addresses = vec![&tx.data.sender];
+        if let Some(receiver) = &tx.data.receiver {
+            if *receiver != tx.data.sender {
+                addresses.push(receiver);
+            }
+        }
+        addresses
+    }
+
+    pub fn get_location(&self, tx_hash: &[u8]) -> Option<TxLocation> {
+        self.transactions.get(tx_hash).copied()
+    }
+
+    pub fn get_address_locations(&self, address: &[u8]) -> &[TxLocation] {
+        self.addresses.get(address).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn get_auction_locations(&self, auction_id: &str) -> &[TxLocation] {
+        self.auctions.get(auction_id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn get_all_auction_locations(&self) -> Vec<TxLocation> {
+        let mut locations: Vec<TxLocation> = self.auctions.values().flatten().copied().collect();
+        locations.sort();
+        locations
+    }
+}
@@ -0,0 +1,6 @@
+use crate::blockchain::block::Block;
+use crate::blockchain::state::ChainState;
+
+pub trait ChainListener: Send {
+    fn on_chain_update(&mut self, disconnected: &[Block], connected: &[Block], state: &ChainState);
+}
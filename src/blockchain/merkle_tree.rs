@@ -1,4 +1,5 @@
 use super::*;
+use crate::blockchain::encoding::{Decode, Decoder, Encode, Encoder};
 use crate::blockchain::transaction::Transaction;
 use sha2::{Digest, Sha256};
 
@@ -96,58 +97,33 @@ impl MerkleTree {
     }
 
     pub fn generate_proof(&self, tx_hash: &[u8]) -> Option<MerkleProof> {
-        let leaf_index = self.leaves.iter().position(|leaf| leaf == tx_hash)?;
-
+        let mut index = self.leaves.iter().position(|leaf| leaf == tx_hash)?;
+        let mut level = self.leaves.clone();
         let mut proof = Vec::new();
-        let current_index = leaf_index;
-        let current_level_size = self.leaves.len();
 
-        self.traverse_for_proof(
-            self.root.as_ref()?,
-            &mut proof,
-            current_index,
-            current_level_size,
-        );
+        loop {
+            if level.len() % 2 == 1 {
+                level.push(level.last().unwrap().clone());
+            }
 
-        Some(MerkleProof { proof })
-    }
+            if index % 2 == 1 {
+                proof.push((level[index - 1].clone(), false));
+            } else {
+                proof.push((level[index + 1].clone(), true));
+            }
 
-    fn traverse_for_proof(
-        &self,
-        node: &MerkleNode,
-        proof: &mut Vec<(MerkleHash, bool)>,
-        index: usize,
-        level_size: usize,
-    ) {
-        if node.left.is_none() && node.right.is_none() {
-            return;
-        }
+            level = level
+                .chunks(2)
+                .map(|pair| Self::hash_pair(&pair[0], &pair[1]))
+                .collect();
+            index /= 2;
 
-        let is_right = index % 2 == 1;
-
-        if let (Some(left), Some(right)) = (&node.left, &node.right) {
-            if is_right {
-                proof.push((left.hash.clone(), false));
-                if let Some(right_node) = &node.right {
-                    self.traverse_for_proof(
-                        right_node,
-                        proof,
-                        index / 2,
-                        (level_size + 1) / 2,
-                    );
-                }
-            } else {
-                proof.push((right.hash.clone(), true));
-                if let Some(left_node) = &node.left {
-                    self.traverse_for_proof(
-                        left_node,
-                        proof,
-                        index / 2,
-                        (level_size + 1) / 2,
-                    );
-                }
+            if level.len() == 1 {
+                break;
             }
         }
+
+        Some(MerkleProof { proof })
     }
 
     pub fn verify_proof(
@@ -232,3 +208,30 @@ impl Hashable for MerkleTree {
         self.get_root_hash().unwrap_or_default()
     }
 }
+
+impl Encode for MerkleProof {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_u32(self.proof.len() as u32);
+        for (hash, is_right) in &self.proof {
+            encoder.write_bytes(hash);
+            encoder.write_u8(*is_right as u8);
+        }
+    }
+}
+
+impl Decode for MerkleProof {
+    fn decode(decoder: &mut Decoder) -> Result<Self, &'static str> {
+        let len = decoder.read_u32()?;
+        let mut proof = Vec::new();
+        for _ in 0..len {
+            let hash = decoder.read_bytes()?;
+            let is_right = match decoder.read_u8()? {
+                0 => false,
+                1 => true,
+                _ => return Err("Invalid proof direction"),
+            };
+            proof.push((hash, is_right));
+        }
+        Ok(MerkleProof { proof })
+    }
+}
@@ -0,0 +1,102 @@
+use super::*;
+use crate::blockchain::block::{Block, BlockHeader};
+use crate::blockchain::target::hash_meets_target;
+use crate::blockchain::template::BlockTemplate;
+use crate::constants::{MAX_MINING_TIME, MINER_CHECK_INTERVAL, TEMPLATE_REFRESH_INTERVAL};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+pub enum MiningResult {
+    Found(Block),
+    Cancelled,
+    TimedOut,
+}
+
+pub struct Miner {
+    threads: usize,
+}
+
+impl Default for Miner {
+    fn default() -> Self {
+        Self::new(thread::available_parallelism().map_or(1, NonZeroUsize::get))
+    }
+}
+
+impl Miner {
+    pub fn new(threads: usize) -> Self {
+        Miner {
+            threads: threads.max(1),
+        }
+    }
+
+    pub fn mine(&self, template: &mut BlockTemplate, cancel: &AtomicBool) -> MiningResult {
+        let start_time = Instant::now();
+
+        loop {
+            if start_time.elapsed() > MAX_MINING_TIME {
+                return MiningResult::TimedOut;
+            }
+
+            let header = template.next_work();
+            if let Some(nonce) = self.search(&header, cancel) {
+                if let Ok(block) = template.solve(header.extra_nonce, header.timestamp, nonce) {
+                    return MiningResult::Found(block);
+                }
+            }
+
+            if cancel.load(Ordering::Relaxed) {
+                return MiningResult::Cancelled;
+            }
+        }
+    }
+
+    pub fn search(&self, header: &BlockHeader, cancel: &AtomicBool) -> Option<u64> {
+        let start_time = Instant::now();
+        let stop = AtomicBool::new(false);
+        let found = Mutex::new(None);
+
+        thread::scope(|scope| {
+            for offset in 0..self.threads as u64 {
+                let mut header = header.clone();
+                let (stop, found) = (&stop, &found);
+                let step = self.threads as u64;
+
+                scope.spawn(move || {
+                    let mut nonce = offset;
+                    let mut checked = 0u64;
+
+                    loop {
+                        header.nonce = nonce;
+                        let hash = header.hash();
+
+                        if hash_meets_target(&hash, header.bits) {
+                            if !stop.swap(true, Ordering::SeqCst) {
+                                *found.lock().unwrap() = Some(nonce);
+                            }
+                            return;
+                        }
+
+                        checked += 1;
+                        if checked.is_multiple_of(MINER_CHECK_INTERVAL)
+                            && (stop.load(Ordering::Relaxed)
+                                || cancel.load(Ordering::Relaxed)
+                                || start_time.elapsed() > TEMPLATE_REFRESH_INTERVAL)
+                        {
+                            return;
+                        }
+
+                        nonce = match nonce.checked_add(step) {
+                            Some(next) => next,
+                            None => return,
+                        };
+                    }
+                });
+            }
+        });
+
+        found.into_inner().unwrap()
+    }
+}
@@ -1,8 +1,21 @@
+pub mod auction_command;
 pub mod block;
+pub mod block_tree;
+#[allow(clippy::module_inception)]
 pub mod blockchain;
+pub mod difficulty;
+pub mod encoding;
+pub mod genesis;
 pub mod hashable;
+pub mod index;
 pub mod lib;
+pub mod listener;
 pub mod merkle_tree;
+pub mod miner;
+pub mod state;
+pub mod storage;
+pub mod target;
+pub mod template;
 pub mod transaction;
 pub mod transaction_pool;
 
@@ -0,0 +1,173 @@
+use super::*;
+use crate::blockchain::block::Block;
+use crate::blockchain::encoding::{Decode, Decoder, Encode, Encoder};
+use crate::blockchain::lib::{u64_to_bytes, BHash};
+use crate::blockchain::merkle_tree::{MerkleProof, MerkleTree};
+use crate::blockchain::transaction::{PublicKey, Transaction, TransactionType};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug)]
+pub struct Account {
+    pub address: PublicKey,
+    pub balance: u64,
+    pub nonce: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccountProof {
+    pub account: Account,
+    pub proof: MerkleProof,
+}
+
+#[derive(Clone, Default)]
+pub struct ChainState {
+    balances: HashMap<PublicKey, u64>,
+    nonces: HashMap<PublicKey, u64>,
+}
+
+impl ChainState {
+    pub fn new(balances: HashMap<PublicKey, u64>) -> Self {
+        ChainState {
+            balances,
+            nonces: HashMap::new(),
+        }
+    }
+
+    pub fn get_balance(&self, address: &PublicKey) -> u64 {
+        *self.balances.get(address).unwrap_or(&0)
+    }
+
+    pub fn get_nonce(&self, address: &PublicKey) -> u64 {
+        *self.nonces.get(address).unwrap_or(&0)
+    }
+
+    pub fn get_next_nonce(&self, address: &PublicKey) -> u64 {
+        self.get_nonce(address) + 1
+    }
+
+    pub fn get_account(&self, address: &PublicKey) -> Option<Account> {
+        self.balances.get(address).map(|balance| Account {
+            address: address.clone(),
+            balance: *balance,
+            nonce: self.get_nonce(address),
+        })
+    }
+
+    fn get_accounts(&self) -> Vec<Account> {
+        let mut addresses: Vec<_> = self.balances.keys().collect();
+        addresses.sort();
+        addresses
+            .into_iter()
+            .filter_map(|address| self.get_account(address))
+            .collect()
+    }
+
+    fn get_merkle_tree(&self) -> MerkleTree {
+        MerkleTree::from_hashes(self.get_accounts().iter().map(|account| account.hash()).collect())
+    }
+
+    pub fn get_root(&self) -> BHash {
+        self.get_merkle_tree().get_root_hash().unwrap_or_else(|| vec![0; 32])
+    }
+
+    pub fn get_proof(&self, address: &PublicKey) -> Option<AccountProof> {
+        let account = self.get_account(address)?;
+        let proof = self.get_merkle_tree().generate_proof(&account.hash())?;
+        Some(AccountProof {
+            account,
+            proof,
+        })
+    }
+
+    pub fn apply_block(&mut self, block: &Block) -> Result<(), &'static str> {
+        for tx in &block.transactions {
+            self.apply_transaction(tx)?;
+        }
+        Ok(())
+    }
+
+    pub fn apply_transaction(&mut self, tx: &Transaction) -> Result<(), &'static str> {
+        if !tx.is_coinbase() && tx.data.nonce != self.get_next_nonce(&tx.data.sender) {
+            return Err("Transaction nonce is out of order");
+        }
+
+        let (cost, credit) = match tx.data.tx_type {
+            TransactionType::Transfer => match tx.data.amount {
+                Some(amount) => (
+                    amount.checked_add(tx.data.fee).ok_or("Transaction amount overflow")?,
+                    tx.data.receiver.as_ref().map(|receiver| (receiver, amount)),
+                ),
+                None => (0, None),
+            },
+            TransactionType::Data => (tx.data.fee, None),
+            TransactionType::Coinbase => match (tx.data.amount, &tx.data.receiver) {
+                (Some(amount), Some(receiver)) => (0, Some((receiver, amount))),
+                _ => (0, None),
+            },
+        };
+
+        let sender_balance = self.get_balance(&tx.data.sender)
+            .checked_sub(cost)
+            .ok_or("Insufficient balance for transaction")?;
+
+        if let Some((receiver, amount)) = credit {
+            let receiver_balance = if *receiver == tx.data.sender {
+                sender_balance
+            } else {
+                self.get_balance(receiver)
+            };
+            receiver_balance.checked_add(amount).ok_or("Balance overflow")?;
+        }
+
+        if !tx.is_coinbase() {
+            self.nonces.insert(tx.data.sender.clone(), tx.data.nonce);
+            self.balances.insert(tx.data.sender.clone(), sender_balance);
+        }
+
+        if let Some((receiver, amount)) = credit {
+            *self.balances.entry(receiver.clone()).or_insert(0) += amount;
+        }
+
+        Ok(())
+    }
+}
+
+impl AccountProof {
+    pub fn verify(&self, state_root: &[u8]) -> bool {
+        MerkleTree::verify_proof(state_root, &self.account.hash(), &self.proof)
+    }
+}
+
+impl Hashable for Account {
+    fn bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+
+        bytes.extend(&self.address);
+        bytes.extend(&u64_to_bytes(&self.balance));
+        bytes.extend(&u64_to_bytes(&self.nonce));
+
+        bytes
+    }
+}
+
+impl Encode for AccountProof {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_bytes(&self.account.address);
+        encoder.write_u64(self.account.balance);
+        encoder.write_u64(self.account.nonce);
+        self.proof.encode(encoder);
+    }
+}
+
+impl Decode for AccountProof {
+    fn decode(decoder: &mut Decoder) -> Result<Self, &'static str> {
+        Ok(AccountProof {
+            account: Account {
+                address: decoder.read_bytes()?,
+                balance: decoder.read_u64()?,
+                nonce: decoder.read_u64()?,
+            },
+            proof: MerkleProof::decode(decoder)?,
+        })
+    }
+}
@@ -0,0 +1,232 @@
+use crate::blockchain::block::Block;
+use crate::blockchain::encoding::{Decode, Encode};
+use crate::blockchain::lib::BHash;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const BLOCKS_FILE: &str = "blocks.dat";
+const INDEX_FILE: &str = "blocks.idx";
+const HASH_LENGTH: usize = 32;
+const INDEX_ENTRY_SIZE: usize = 8 + 4 + HASH_LENGTH;
+
+pub trait BlockStore: Send {
+    fn append(&mut self, block: &Block) -> io::Result<()>;
+
+    fn truncate(&mut self, height: usize) -> io::Result<()>;
+
+    fn get_by_height(&self, height: usize) -> io::Result<Option<Block>>;
+
+    fn get_by_hash(&self, hash: &[u8]) -> io::Result<Option<Block>>;
+
+    fn height(&self) -> usize;
+
+    fn load_all(&self) -> io::Result<Vec<Block>> {
+        let mut blocks = Vec::with_capacity(self.height());
+        for height in 0..self.height() {
+            match self.get_by_height(height) {
+                Ok(Some(block)) => blocks.push(block),
+                Ok(None) => break,
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(blocks)
+    }
+}
+
+pub struct MemoryBlockStore {
+    blocks: Vec<Block>,
+}
+
+impl MemoryBlockStore {
+    pub fn new() -> Self {
+        MemoryBlockStore {
+            blocks: Vec::new(),
+        }
+    }
+}
+
+impl Default for MemoryBlockStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockStore for MemoryBlockStore {
+    fn append(&mut self, block: &Block) -> io::Result<()> {
+        self.blocks.push(block.clone());
+        Ok(())
+    }
+
+    fn truncate(&mut self, height: usize) -> io::Result<()> {
+        self.blocks.truncate(height);
+        Ok(())
+    }
+
+    fn get_by_height(&self, height: usize) -> io::Result<Option<Block>> {
+        Ok(self.blocks.get(height).cloned())
+    }
+
+    fn get_by_hash(&self, hash: &[u8]) -> io::Result<Option<Block>> {
+        Ok(self.blocks.iter().find(|block| block.hash == hash).cloned())
+    }
+
+    fn height(&self) -> usize {
+        self.blocks.len()
+    }
+}
+
+struct IndexEntry {
+    offset: u64,
+    length: u32,
+    hash: BHash,
+}
+
+impl IndexEntry {
+    fn to_bytes(&self) -> [u8; INDEX_ENTRY_SIZE] {
+        let mut bytes = [0u8; INDEX_ENTRY_SIZE];
+        bytes[0..8].copy_from_slice(&self.offset.to_be_bytes());
+        bytes[8..12].copy_from_slice(&self.length.to_be_bytes());
+        bytes[12..].copy_from_slice(&self.hash);
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        IndexEntry {
+            offset: u64::from_be_bytes(bytes[0..8].try_into().unwrap()),
+            length: u32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+            hash: bytes[12..INDEX_ENTRY_SIZE].to_vec(),
+        }
+    }
+
+    fn end(&self) -> u64 {
+        self.offset + self.length as u64
+    }
+}
+
+pub struct FileBlockStore {
+    data: File,
+    index: File,
+    entries: Vec<IndexEntry>,
+    by_hash: HashMap<BHash, usize>,
+}
+
+impl FileBlockStore {
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        let data = Self::open_file(&dir.join(BLOCKS_FILE))?;
+        let mut index = Self::open_file(&dir.join(INDEX_FILE))?;
+
+        let mut raw_index = Vec::new();
+        index.read_to_end(&mut raw_index)?;
+
+        let data_len = data.metadata()?.len();
+        let mut entries = Vec::new();
+        let mut by_hash = HashMap::new();
+
+        for chunk in raw_index.chunks_exact(INDEX_ENTRY_SIZE) {
+            let entry = IndexEntry::from_bytes(chunk);
+            if entry.end() > data_len {
+                break;
+            }
+            by_hash.insert(entry.hash.clone(), entries.len());
+            entries.push(entry);
+        }
+
+        let mut store = FileBlockStore {
+            data,
+            index,
+            entries,
+            by_hash,
+        };
+
+        let height = store.entries.len();
+        store.truncate(height)?;
+
+        Ok(store)
+    }
+
+    fn open_file(path: &Path) -> io::Result<File> {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+    }
+
+    fn read_entry(&self, entry: &IndexEntry) -> io::Result<Block> {
+        let mut data = &self.data;
+        data.seek(SeekFrom::Start(entry.offset))?;
+
+        let mut bytes = vec![0u8; entry.length as usize];
+        data.read_exact(&mut bytes)?;
+
+        Block::from_bytes(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl BlockStore for FileBlockStore {
+    fn append(&mut self, block: &Block) -> io::Result<()> {
+        if block.hash.len() != HASH_LENGTH {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid block hash length"));
+        }
+
+        let bytes = block.to_bytes();
+        let entry = IndexEntry {
+            offset: self.entries.last().map_or(0, |e| e.end()),
+            length: bytes.len() as u32,
+            hash: block.hash.clone(),
+        };
+
+        self.data.seek(SeekFrom::Start(entry.offset))?;
+        self.data.write_all(&bytes)?;
+        self.data.sync_data()?;
+
+        self.index.seek(SeekFrom::Start((self.entries.len() * INDEX_ENTRY_SIZE) as u64))?;
+        self.index.write_all(&entry.to_bytes())?;
+        self.index.sync_data()?;
+
+        self.by_hash.insert(entry.hash.clone(), self.entries.len());
+        self.entries.push(entry);
+
+        Ok(())
+    }
+
+    fn truncate(&mut self, height: usize) -> io::Result<()> {
+        for entry in self.entries.drain(height.min(self.entries.len())..) {
+            self.by_hash.remove(&entry.hash);
+        }
+
+        let data_len = self.entries.last().map_or(0, |e| e.end());
+        self.data.set_len(data_len)?;
+        self.data.sync_data()?;
+
+        self.index.set_len((self.entries.len() * INDEX_ENTRY_SIZE) as u64)?;
+        self.index.sync_data()?;
+
+        Ok(())
+    }
+
+    fn get_by_height(&self, height: usize) -> io::Result<Option<Block>> {
+        match self.entries.get(height) {
+            Some(entry) => self.read_entry(entry).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn get_by_hash(&self, hash: &[u8]) -> io::Result<Option<Block>> {
+        match self.by_hash.get(hash) {
+            Some(&height) => self.get_by_height(height),
+            None => Ok(None),
+        }
+    }
+
+    fn height(&self) -> usize {
+        self.entries.len()
+    }
+}
@@ -0,0 +1,295 @@
+use std::fmt::{self, Debug, Formatter};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const ONE: U256 = U256([0, 0, 0, 1]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    pub fn from_u64(n: u64) -> Self {
+        U256([0, 0, 0, n])
+    }
+
+    pub fn from_be_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() > 32 {
+            return None;
+        }
+
+        let mut padded = [0u8; 32];
+        padded[32 - bytes.len()..].copy_from_slice(bytes);
+
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(padded.chunks_exact(8)) {
+            *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        Some(U256(limbs))
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (chunk, limb) in bytes.chunks_exact_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_compact(bits: u32) -> Option<Self> {
+        let exponent = bits >> 24;
+        let mantissa = bits & 0x007f_ffff;
+
+        if bits & 0x0080_0000 != 0 || mantissa == 0 || exponent > 32 {
+            return None;
+        }
+
+        let target = if exponent <= 3 {
+            U256::from_u64((mantissa >> (8 * (3 - exponent))) as u64)
+        } else {
+            U256::from_u64(mantissa as u64).shl(8 * (exponent - 3))
+        };
+
+        if target == U256::ZERO {
+            return None;
+        }
+        Some(target)
+    }
+
+    pub fn to_compact(&self) -> u32 {
+        let mut size = self.bytes();
+        let mut compact = if size <= 3 {
+            (self.0[3] << (8 * (3 - size))) as u32
+        } else {
+            self.shr(8 * (size - 3)).0[3] as u32
+        };
+
+        if compact & 0x0080_0000 != 0 {
+            compact >>= 8;
+            size += 1;
+        }
+        compact | (size << 24)
+    }
+
+    pub fn bits(&self) -> u32 {
+        256 - self.leading_zeros()
+    }
+
+    pub fn leading_zeros(&self) -> u32 {
+        let mut zeros = 0;
+        for limb in self.0 {
+            zeros += limb.leading_zeros();
+            if limb != 0 {
+                break;
+            }
+        }
+        zeros
+    }
+
+    pub fn shl(&self, shift: u32) -> Self {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+
+        let limbs = (shift / 64) as usize;
+        let offset = shift % 64;
+        let mut result = [0u64; 4];
+        for (i, limb) in result.iter_mut().enumerate().take(4 - limbs) {
+            *limb = self.0[i + limbs] << offset;
+            if offset > 0 && i + limbs + 1 < 4 {
+                *limb |= self.0[i + limbs + 1] >> (64 - offset);
+            }
+        }
+        U256(result)
+    }
+
+    pub fn shr(&self, shift: u32) -> Self {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+
+        let limbs = (shift / 64) as usize;
+        let offset = shift % 64;
+        let mut result = [0u64; 4];
+        for (i, limb) in result.iter_mut().enumerate().skip(limbs) {
+            *limb = self.0[i - limbs] >> offset;
+            if offset > 0 && i > limbs {
+                *limb |= self.0[i - limbs - 1] << (64 - offset);
+            }
+        }
+        U256(result)
+    }
+
+    pub fn saturating_add(&self, other: &U256) -> Self {
+        let mut result = [0u64; 4];
+        let mut carry = false;
+        for i in (0..4).rev() {
+            let (sum, c1) = self.0[i].overflowing_add(other.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            result[i] = sum;
+            carry = c1 || c2;
+        }
+
+        if carry {
+            return U256::MAX;
+        }
+        U256(result)
+    }
+
+    fn wrapping_sub(&self, other: &U256) -> Self {
+        let mut result = [0u64; 4];
+        let mut borrow = false;
+        for i in (0..4).rev() {
+            let (diff, b1) = self.0[i].overflowing_sub(other.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            result[i] = diff;
+            borrow = b1 || b2;
+        }
+        U256(result)
+    }
+
+    pub fn saturating_mul_u64(&self, n: u64) -> Self {
+        let mut result = [0u64; 4];
+        let mut carry = 0u128;
+        for i in (0..4).rev() {
+            let product = self.0[i] as u128 * n as u128 + carry;
+            result[i] = product as u64;
+            carry = product >> 64;
+        }
+
+        if carry != 0 {
+            return U256::MAX;
+        }
+        U256(result)
+    }
+
+    pub fn div_u64(&self, n: u64) -> Self {
+        let mut result = [0u64; 4];
+        let mut remainder = 0u128;
+        for (quotient, limb) in result.iter_mut().zip(self.0) {
+            let dividend = (remainder << 64) | limb as u128;
+            *quotient = (dividend / n as u128) as u64;
+            remainder = dividend % n as u128;
+        }
+        U256(result)
+    }
+
+    pub fn checked_div(&self, divisor: &U256) -> Option<Self> {
+        if *divisor == U256::ZERO {
+            return None;
+        }
+
+        let mut quotient = U256::ZERO;
+        let mut remainder = U256::ZERO;
+        for bit in (0..self.bits()).rev() {
+            remainder = remainder.shl(1);
+            if self.bit(bit) {
+                remainder.0[3] |= 1;
+            }
+            if remainder >= *divisor {
+                remainder = remainder.wrapping_sub(divisor);
+                quotient.0[3 - (bit / 64) as usize] |= 1 << (bit % 64);
+            }
+        }
+        Some(quotient)
+    }
+
+    fn bytes(&self) -> u32 {
+        self.bits().div_ceil(8)
+    }
+
+    fn bit(&self, index: u32) -> bool {
+        self.0[3 - (index / 64) as usize] & (1 << (index % 64)) != 0
+    }
+
+    fn not(&self) -> Self {
+        U256(self.0.map(|limb| !limb))
+    }
+}
+
+impl Debug for U256 {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.to_be_bytes()))
+    }
+}
+
+pub fn hash_meets_target(hash: &[u8], bits: u32) -> bool {
+    match (U256::from_be_bytes(hash), U256::from_compact(bits)) {
+        (Some(hash), Some(target)) => hash <= target,
+        _ => false,
+    }
+}
+
+pub fn work_from_bits(bits: u32) -> U256 {
+    let Some(target) = U256::from_compact(bits) else {
+        return U256::ZERO;
+    };
+
+    let divisor = target.saturating_add(&U256::ONE);
+    target.not().checked_div(&divisor).unwrap_or_default().saturating_add(&U256::ONE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_round_trips() {
+        for bits in [0x1d00_ffff, 0x1f00_ffff, 0x200f_ffff, 0x1b04_04cb, 0x0312_3456, 0x0500_9234, 0x0112_0000] {
+            assert_eq!(U256::from_compact(bits).unwrap().to_compact(), bits);
+        }
+    }
+
+    #[test]
+    fn compact_matches_bitcoin_genesis_target() {
+        let target = hex::decode("00000000ffff0000000000000000000000000000000000000000000000000000").unwrap();
+        assert_eq!(U256::from_compact(0x1d00_ffff), U256::from_be_bytes(&target));
+        assert_eq!(work_from_bits(0x1d00_ffff), U256::from_u64(0x1_0001_0001));
+    }
+
+    #[test]
+    fn compact_rejects_invalid_targets() {
+        assert_eq!(U256::from_compact(0x1d80_ffff), None);
+        assert_eq!(U256::from_compact(0x1d00_0000), None);
+        assert_eq!(U256::from_compact(0x2100_ffff), None);
+        assert_eq!(U256::from_compact(0x0100_3456), None);
+        assert!(!hash_meets_target(&[0; 32], 0x1d80_ffff));
+    }
+
+    #[test]
+    fn work_increases_with_difficulty() {
+        let easy = work_from_bits(0x200f_ffff);
+        let medium = work_from_bits(0x1f00_ffff);
+        let hard = work_from_bits(0x1d00_ffff);
+
+        assert!(U256::ZERO < easy && easy < medium && medium < hard);
+        assert_eq!(work_from_bits(0x1d80_ffff), U256::ZERO);
+    }
+
+    #[test]
+    fn shifts_cross_limbs() {
+        assert_eq!(U256::ONE.shl(64), U256([0, 0, 1, 0]));
+        assert_eq!(U256::ONE.shl(255).shr(255), U256::ONE);
+        assert_eq!(U256::from_u64(u64::MAX).shl(8).shr(8), U256::from_u64(u64::MAX));
+        assert_eq!(U256::from_u64(0xff).shl(60), U256([0, 0, 0xf, 0xf000_0000_0000_0000]));
+        assert_eq!(U256::MAX.shr(192), U256::from_u64(u64::MAX));
+        assert_eq!(U256::MAX.shl(256), U256::ZERO);
+        assert_eq!(U256::MAX.shr(256), U256::ZERO);
+    }
+
+    #[test]
+    fn checked_div_matches_long_division() {
+        assert_eq!(U256::ONE.checked_div(&U256::ZERO), None);
+        assert_eq!(U256::from_u64(100).checked_div(&U256::from_u64(7)), Some(U256::from_u64(14)));
+        assert_eq!(U256::MAX.checked_div(&U256::ONE), Some(U256::MAX));
+        assert_eq!(U256::MAX.checked_div(&U256::MAX), Some(U256::ONE));
+        assert_eq!(U256::ONE.shl(200).checked_div(&U256::ONE.shl(100)), Some(U256::ONE.shl(100)));
+        assert_eq!(U256::MAX.checked_div(&U256::from_u64(12_345)), Some(U256::MAX.div_u64(12_345)));
+    }
+
+    #[test]
+    fn arithmetic_saturates() {
+        assert_eq!(U256::MAX.saturating_add(&U256::ONE), U256::MAX);
+        assert_eq!(U256::MAX.saturating_mul_u64(2), U256::MAX);
+        assert_eq!(U256::from_u64(u64::MAX).saturating_add(&U256::ONE), U256::ONE.shl(64));
+    }
+}
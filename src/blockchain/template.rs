@@ -0,0 +1,89 @@
+use super::*;
+use crate::blockchain::block::{Block, BlockHeader};
+use crate::blockchain::lib::{now, BHash};
+use crate::blockchain::target::hash_meets_target;
+use crate::constants::{MAX_BLOCK_TIME, MIN_BLOCK_TIME};
+
+#[derive(Clone)]
+pub struct BlockTemplate {
+    block: Block,
+    parent_timestamp: u128,
+}
+
+impl BlockTemplate {
+    pub fn new(block: Block, parent_timestamp: u128) -> Self {
+        let mut template = BlockTemplate {
+            block,
+            parent_timestamp,
+        };
+        template.refresh_timestamp();
+        template
+    }
+
+    pub fn get_id(&self) -> BHash {
+        self.hash()
+    }
+
+    pub fn get_block(&self) -> &Block {
+        &self.block
+    }
+
+    pub fn get_header(&self) -> BlockHeader {
+        self.block.get_header()
+    }
+
+    pub fn get_prev_hash(&self) -> &[u8] {
+        &self.block.prev_hash
+    }
+
+    pub fn refresh_timestamp(&mut self) {
+        let earliest = self.parent_timestamp + MIN_BLOCK_TIME;
+        let latest = if self.block.index > 1 {
+            self.parent_timestamp + MAX_BLOCK_TIME
+        } else {
+            u128::MAX
+        };
+        self.block.timestamp = now().clamp(earliest, latest);
+    }
+
+    pub fn next_work(&mut self) -> BlockHeader {
+        self.refresh_timestamp();
+        self.block.extra_nonce = self.block.extra_nonce.wrapping_add(1);
+        self.block.nonce = 0;
+        self.get_header()
+    }
+
+    pub fn is_timestamp_valid(&self, timestamp: u128) -> bool {
+        let time_diff = timestamp.saturating_sub(self.parent_timestamp);
+        time_diff >= MIN_BLOCK_TIME && (self.block.index <= 1 || time_diff <= MAX_BLOCK_TIME)
+    }
+
+    pub fn solve(&self, extra_nonce: u64, timestamp: u128, nonce: u64) -> Result<Block, &'static str> {
+        if !self.is_timestamp_valid(timestamp) {
+            return Err("Solution timestamp is out of range");
+        }
+
+        let mut block = self.block.clone();
+        block.extra_nonce = extra_nonce;
+        block.timestamp = timestamp;
+        block.nonce = nonce;
+        block.hash = block.hash();
+
+        if !hash_meets_target(&block.hash, block.bits) {
+            return Err("Solution doesn't meet difficulty requirements");
+        }
+
+        Ok(block)
+    }
+}
+
+impl Hashable for BlockTemplate {
+    fn bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+
+        bytes.extend(&self.block.prev_hash);
+        bytes.extend(&self.block.merkle_root);
+
+        bytes
+    }
+}
@@ -1,5 +1,8 @@
 use super::*;
+use crate::blockchain::auction_command::AuctionCommand;
+use crate::blockchain::encoding::{canonical_serde, Decode, Decoder, Encode, Encoder};
 use crate::blockchain::lib::now;
+use crate::constants::{MAX_FUTURE_TX_TIME, MAX_TX_AGE, TX_VALIDITY_WINDOW};
 use ed25519_dalek::{
     Keypair, PublicKey as DalekPublicKey, Signature as DalekSignature, Signer, Verifier,
 };
@@ -16,10 +19,12 @@ pub type Signature = Vec<u8>;
 pub enum TransactionType {
     Transfer,
     Data,
+    Coinbase,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
 pub struct TransactionData {
+    pub chain_id: String,
     pub sender: PublicKey,
     pub receiver: Option<PublicKey>,
     pub timestamp: u128,
@@ -31,39 +36,15 @@ pub struct TransactionData {
     pub valid_until: Option<u128>,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone)]
 pub struct Transaction {
     pub data: TransactionData,
     pub signature: Signature,
     pub tx_hash: TxHash,
 }
 
-pub struct NonceTracker {
-    nonces: HashMap<PublicKey, u64>,
-}
-
-impl NonceTracker {
-    pub fn new() -> Self {
-        NonceTracker {
-            nonces: HashMap::new(),
-        }
-    }
-
-    pub fn validate_and_update(&mut self, sender: &PublicKey, tx_nonce: u64) -> bool {
-        let current_nonce = self.nonces.get(sender).cloned().unwrap_or(0);
-
-        if tx_nonce != current_nonce + 1 {
-            return false;
-        }
-
-        self.nonces.insert(sender.clone(), tx_nonce);
-        true
-    }
-
-    pub fn get_nonce(&self, sender: &PublicKey) -> u64 {
-        self.nonces.get(sender).cloned().unwrap_or(0)
-    }
-}
+canonical_serde!(TransactionData);
+canonical_serde!(Transaction);
 
 impl Debug for Transaction {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
@@ -80,16 +61,87 @@ impl Debug for Transaction {
 
 impl Hashable for Transaction {
     fn bytes(&self) -> Vec<u8> {
-        let mut bytes = vec![];
-        let data_bytes = serde_json::to_vec(&self.data).unwrap_or_default();
-        bytes.extend(data_bytes);
+        let mut bytes = self.data.to_bytes();
         bytes.extend(&self.signature);
         bytes
     }
 }
 
+impl Encode for TransactionType {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_u8(match self {
+            TransactionType::Transfer => 0,
+            TransactionType::Data => 1,
+            TransactionType::Coinbase => 2,
+        });
+    }
+}
+
+impl Decode for TransactionType {
+    fn decode(decoder: &mut Decoder) -> Result<Self, &'static str> {
+        match decoder.read_u8()? {
+            0 => Ok(TransactionType::Transfer),
+            1 => Ok(TransactionType::Data),
+            2 => Ok(TransactionType::Coinbase),
+            _ => Err("Unknown transaction type"),
+        }
+    }
+}
+
+impl Encode for TransactionData {
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_str(&self.chain_id);
+        encoder.write_bytes(&self.sender);
+        encoder.write_option(&self.receiver, |e, receiver| e.write_bytes(receiver));
+        encoder.write_u128(self.timestamp);
+        self.tx_type.encode(encoder);
+        encoder.write_option(&self.amount, |e, amount| e.write_u64(*amount));
+        encoder.write_option(&self.data, |e, data| e.write_str(data));
+        encoder.write_u64(self.nonce);
+        encoder.write_u64(self.fee);
+        encoder.write_option(&self.valid_until, |e, valid_until| e.write_u128(*valid_until));
+    }
+}
+
+impl Decode for TransactionData {
+    fn decode(decoder: &mut Decoder) -> Result<Self, &'static str> {
+        Ok(TransactionData {
+            chain_id: decoder.read_string()?,
+            sender: decoder.read_bytes()?,
+            receiver: decoder.read_option(Decoder::read_bytes)?,
+            timestamp: decoder.read_u128()?,
+            tx_type: TransactionType::decode(decoder)?,
+            amount: decoder.read_option(Decoder::read_u64)?,
+            data: decoder.read_option(Decoder::read_string)?,
+            nonce: decoder.read_u64()?,
+            fee: decoder.read_u64()?,
+            valid_until: decoder.read_option(Decoder::read_u128)?,
+        })
+    }
+}
+
+impl Encode for Transaction {
+    fn encode(&self, encoder: &mut Encoder) {
+        self.data.encode(encoder);
+        encoder.write_bytes(&self.signature);
+        encoder.write_bytes(&self.tx_hash);
+    }
+}
+
+impl Decode for Transaction {
+    fn decode(decoder: &mut Decoder) -> Result<Self, &'static str> {
+        Ok(Transaction {
+            data: TransactionData::decode(decoder)?,
+            signature: decoder.read_bytes()?,
+            tx_hash: decoder.read_bytes()?,
+        })
+    }
+}
+
 impl Transaction {
+    #[allow(clippy::too_many_arguments)]
     pub fn new_data(
+        chain_id: &str,
         sender: PublicKey,
         receiver: Option<PublicKey>,
         tx_type: TransactionType,
@@ -99,6 +151,7 @@ impl Transaction {
         fee: u64,
     ) -> TransactionData {
         TransactionData {
+            chain_id: chain_id.to_string(),
             sender,
             receiver,
             timestamp: now(),
@@ -107,13 +160,12 @@ impl Transaction {
             data,
             nonce,
             fee,
-            valid_until: Some(now() + 3_600_000),
+            valid_until: Some(now() + TX_VALIDITY_WINDOW),
         }
     }
 
     pub fn sign(tx_data: &TransactionData, key_pair: &Keypair) -> Signature {
-        let data_bytes = serde_json::to_vec(tx_data).unwrap_or_default();
-        key_pair.sign(&data_bytes).to_bytes().to_vec()
+        key_pair.sign(&tx_data.to_bytes()).to_bytes().to_vec()
     }
 
     pub fn create_signed(tx_data: TransactionData, key_pair: &Keypair) -> Self {
@@ -128,27 +180,40 @@ impl Transaction {
         transaction
     }
 
-    pub fn verify(&self) -> bool {
-        if let Some(valid_until) = self.data.valid_until {
-            if now() > valid_until {
-                return false;
-            }
+    pub fn verify(&self, chain_id: &str) -> bool {
+        if self.data.chain_id != chain_id {
+            return false;
         }
 
-        let current_time = now();
-        if self.data.timestamp > current_time + 3_600_000 {
+        if self.tx_hash != self.hash() {
             return false;
         }
 
-        if self.data.timestamp < current_time.saturating_sub(86_400_000) {
+        if !self.validate_transaction_specifics() {
             return false;
         }
 
-        if !self.validate_transaction_specifics() {
+        self.verify_signature()
+    }
+
+    pub fn is_fresh(&self) -> bool {
+        let current_time = now();
+
+        if let Some(valid_until) = self.data.valid_until {
+            if current_time > valid_until {
+                return false;
+            }
+        }
+
+        if self.data.timestamp > current_time + MAX_FUTURE_TX_TIME {
             return false;
         }
 
-        let data_bytes = serde_json::to_vec(&self.data).unwrap_or_default();
+        self.data.timestamp >= current_time.saturating_sub(MAX_TX_AGE)
+    }
+
+    pub fn verify_signature(&self) -> bool {
+        let data_bytes = self.data.to_bytes();
 
         if let Ok(public_key) = DalekPublicKey::from_bytes(&self.data.sender) {
             if let Ok(signature) = DalekSignature::from_bytes(&self.signature) {
@@ -193,6 +258,15 @@ impl Transaction {
                     return false;
                 }
             }
+            TransactionType::Coinbase => {
+                if self.data.amount.is_none() || self.data.data.is_some() || self.data.fee != 0 {
+                    return false;
+                }
+
+                if self.data.receiver.as_ref().is_none_or(|receiver| receiver.len() != 32) {
+                    return false;
+                }
+            }
         }
 
         if let Some(ref data) = self.data.data {
@@ -219,6 +293,7 @@ impl Transaction {
 
     pub fn create_transfer(
         key_pair: &Keypair,
+        chain_id: &str,
         receiver: PublicKey,
         amount: u64,
         nonce: u64,
@@ -235,6 +310,7 @@ impl Transaction {
         let sender = Self::get_public_key(key_pair);
 
         let tx_data = TransactionData {
+            chain_id: chain_id.to_string(),
             sender,
             receiver: Some(receiver),
             timestamp: now(),
@@ -243,14 +319,50 @@ impl Transaction {
             data: None,
             nonce,
             fee,
-            valid_until: Some(now() + 3_600_000),
+            valid_until: Some(now() + TX_VALIDITY_WINDOW),
         };
 
         Ok(Self::create_signed(tx_data, key_pair))
     }
 
+    pub fn create_coinbase(key_pair: &Keypair, chain_id: &str, height: u32, reward: u64) -> Self {
+        let miner = Self::get_public_key(key_pair);
+
+        let tx_data = TransactionData {
+            chain_id: chain_id.to_string(),
+            sender: miner.clone(),
+            receiver: Some(miner),
+            timestamp: now(),
+            tx_type: TransactionType::Coinbase,
+            amount: Some(reward),
+            data: None,
+            nonce: height as u64,
+            fee: 0,
+            valid_until: None,
+        };
+
+        Self::create_signed(tx_data, key_pair)
+    }
+
+    pub fn get_auction_command(&self) -> Option<AuctionCommand> {
+        if self.data.tx_type != TransactionType::Data {
+            return None;
+        }
+
+        AuctionCommand::parse(self.data.data.as_ref()?)
+    }
+
+    pub fn get_auction_id(&self) -> Option<String> {
+        self.get_auction_command().map(|command| command.get_id().to_string())
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        self.data.tx_type == TransactionType::Coinbase
+    }
+
     pub fn create_data_tx(
         key_pair: &Keypair,
+        chain_id: &str,
         data: String,
         nonce: u64,
         fee: u64,
@@ -271,6 +383,7 @@ impl Transaction {
         let sender = Self::get_public_key(key_pair);
 
         let tx_data = TransactionData {
+            chain_id: chain_id.to_string(),
             sender,
             receiver: None,
             timestamp: now(),
@@ -279,7 +392,7 @@ impl Transaction {
             data: Some(sanitized_data),
             nonce,
             fee,
-            valid_until: Some(now() + 3_600_000),
+            valid_until: Some(now() + TX_VALIDITY_WINDOW),
         };
 
         Ok(Self::create_signed(tx_data, key_pair))
@@ -304,6 +417,7 @@ impl Transaction {
                 let sender_balance = balances.get(&self.data.sender).unwrap_or(&0);
                 return *sender_balance >= self.data.fee;
             }
+            TransactionType::Coinbase => return true,
         }
         false
     }
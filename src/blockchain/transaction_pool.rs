@@ -1,6 +1,9 @@
-use crate::blockchain::transaction::{NonceTracker, PublicKey, Transaction, TransactionType, TxHash};
-use crate::constants::{MAX_POOL_SIZE, MAX_TXS_PER_SENDER, MIN_FEE_RATE};
-use std::collections::{BTreeMap, HashMap};
+use crate::blockchain::block::Block;
+use crate::blockchain::listener::ChainListener;
+use crate::blockchain::state::ChainState;
+use crate::blockchain::transaction::{PublicKey, Transaction, TransactionType, TxHash};
+use crate::constants::{MAX_POOL_SIZE, MAX_SEEN_TRANSACTIONS, MAX_TXS_PER_SENDER};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::time::Instant;
 
 #[derive(Clone)]
@@ -11,20 +14,24 @@ pub struct PoolTransaction {
 }
 
 pub struct TransactionPool {
+    chain_id: String,
     transactions: HashMap<TxHash, PoolTransaction>,
     by_sender: HashMap<PublicKey, BTreeMap<u64, TxHash>>,
-    nonce_tracker: NonceTracker,
     sender_counts: HashMap<PublicKey, usize>,
+    seen: HashSet<TxHash>,
+    seen_order: VecDeque<TxHash>,
     total_size: usize,
 }
 
 impl TransactionPool {
-    pub fn new() -> Self {
+    pub fn new(chain_id: String) -> Self {
         TransactionPool {
+            chain_id,
             transactions: HashMap::new(),
             by_sender: HashMap::new(),
-            nonce_tracker: NonceTracker::new(),
             sender_counts: HashMap::new(),
+            seen: HashSet::new(),
+            seen_order: VecDeque::new(),
             total_size: 0,
         }
     }
@@ -34,10 +41,22 @@ impl TransactionPool {
             self.remove_lowest_fee_transaction()?;
         }
 
-        if !tx.verify() {
+        if tx.is_coinbase() {
+            return Err("Coinbase transactions cannot be submitted to the pool");
+        }
+
+        if tx.data.chain_id != self.chain_id {
+            return Err("Transaction belongs to a different chain");
+        }
+
+        if !tx.verify(&self.chain_id) {
             return Err("Transaction signature is invalid");
         }
 
+        if !tx.is_fresh() {
+            return Err("Transaction is expired or its timestamp is out of range");
+        }
+
         if self.transactions.contains_key(&tx.tx_hash) {
             return Err("Transaction already exists in the pool");
         }
@@ -62,9 +81,6 @@ impl TransactionPool {
 
         let tx_size = self.estimate_transaction_size(&tx);
         let fee_per_byte = tx.data.fee / tx_size;
-        if fee_per_byte < MIN_FEE_RATE {
-            return Err("Transaction fee too low");
-        }
 
         let pool_tx = PoolTransaction {
             transaction: tx.clone(),
@@ -76,13 +92,15 @@ impl TransactionPool {
 
         self.by_sender
             .entry(sender.clone())
-            .or_insert_with(BTreeMap::new)
+            .or_default()
             .insert(tx.data.nonce, tx.tx_hash.clone());
 
         *self.sender_counts.entry(sender.clone()).or_insert(0) += 1;
 
         self.total_size += tx_size as usize;
 
+        self.mark_seen(&tx.tx_hash);
+
         Ok(())
     }
 
@@ -107,7 +125,7 @@ impl TransactionPool {
 
             self.total_size = self
                 .total_size
-                .saturating_sub(self.estimate_transaction_size(&tx) as usize);
+                .saturating_sub(self.estimate_transaction_size(tx) as usize);
 
             return Some(pool_tx.transaction);
         }
@@ -127,6 +145,27 @@ impl TransactionPool {
             .collect()
     }
 
+    pub fn get_inventory(&self) -> Vec<TxHash> {
+        self.by_sender.values().flat_map(|sender_txs| sender_txs.values().cloned()).collect()
+    }
+
+    pub fn has_seen(&self, tx_hash: &TxHash) -> bool {
+        self.seen.contains(tx_hash)
+    }
+
+    fn mark_seen(&mut self, tx_hash: &TxHash) {
+        if !self.seen.insert(tx_hash.clone()) {
+            return;
+        }
+
+        self.seen_order.push_back(tx_hash.clone());
+        if self.seen_order.len() > MAX_SEEN_TRANSACTIONS {
+            if let Some(oldest) = self.seen_order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+    }
+
     pub fn size(&self) -> usize {
         self.transactions.len()
     }
@@ -138,65 +177,56 @@ impl TransactionPool {
         self.total_size = 0;
     }
 
-    pub fn get_transactions_for_block(&self, max_size: usize, max_gas: u64) -> Vec<Transaction> {
+    pub fn get_transactions_for_block(&self, state: &ChainState, max_size: usize, max_gas: u64) -> Vec<Transaction> {
+        let mut state = state.clone();
         let mut selected = Vec::new();
         let mut total_size = 0;
         let mut total_gas = 0;
 
-        let mut pool_txs: Vec<_> = self.transactions.values().collect();
-        pool_txs.sort_by(|a, b| {
-            b.fee_per_byte
-                .cmp(&a.fee_per_byte)
-                .then_with(|| a.transaction.data.nonce.cmp(&b.transaction.data.nonce))
-        });
-
-        let mut sender_nonces: HashMap<PublicKey, u64> = HashMap::new();
+        let mut queues: Vec<VecDeque<&PoolTransaction>> = self
+            .by_sender
+            .iter()
+            .map(|(sender, sender_txs)| {
+                sender_txs
+                    .range(state.get_next_nonce(sender)..)
+                    .filter_map(|(_, hash)| self.transactions.get(hash))
+                    .collect()
+            })
+            .collect();
+
+        while selected.len() < 1000 {
+            let best = queues
+                .iter()
+                .enumerate()
+                .filter_map(|(i, queue)| queue.front().map(|pool_tx| (i, pool_tx.fee_per_byte)))
+                .max_by_key(|&(_, fee_per_byte)| fee_per_byte);
+
+            let Some((i, _)) = best else {
+                break;
+            };
 
-        for pool_tx in pool_txs {
-            let tx = &pool_tx.transaction;
+            let tx = &queues[i].pop_front().unwrap().transaction;
             let tx_size = self.estimate_transaction_size(tx);
             let tx_gas = self.estimate_gas_cost(tx);
 
-            if total_size + tx_size > max_size as u64 || total_gas + tx_gas > max_gas {
+            if total_size + tx_size > max_size as u64
+                || total_gas + tx_gas > max_gas
+                || state.apply_transaction(tx).is_err()
+            {
+                queues[i].clear();
                 continue;
             }
 
-            let sender = &tx.data.sender;
-
-            let base_nonce = self.nonce_tracker.get_nonce(sender);
-            let current_nonce = sender_nonces.get(sender).unwrap_or(&base_nonce);
-            let expected_nonce = current_nonce + 1;
-
-            let should_include = if tx.data.nonce == expected_nonce {
-                true
-            } else if base_nonce == 0 && tx.data.nonce == 1 {
-                true
-            } else if tx.data.nonce > expected_nonce && tx.data.nonce <= expected_nonce + 5 {
-                true
-            } else {
-                // To avoid replay attacks, usually this would be set to false.
-                // Since there is no money involved in the transactions, there is no need to worry about it.
-                true
-            };
-
-            if should_include {
-                selected.push(tx.clone());
-                total_size += tx_size;
-                total_gas += tx_gas;
-                sender_nonces.insert(sender.clone(), tx.data.nonce);
-
-                if selected.len() >= 1000 {
-                    break;
-                }
-            }
+            selected.push(tx.clone());
+            total_size += tx_size;
+            total_gas += tx_gas;
         }
 
         selected
     }
 
-    pub fn get_transactions_4_block(&self, max_transactions: usize) -> Vec<Transaction> {
-        let result = self.get_transactions_for_block(max_transactions * 500, 1000000);
-        result
+    pub fn get_transactions_4_block(&self, state: &ChainState, max_transactions: usize) -> Vec<Transaction> {
+        self.get_transactions_for_block(state, max_transactions * 500, 1000000)
     }
 
     fn remove_lowest_fee_transaction(&mut self) -> Result<(), &'static str> {
@@ -221,33 +251,19 @@ impl TransactionPool {
             TransactionType::Data => {
                 21000 + tx.data.data.as_ref().map_or(0, |d| d.len() as u64 * 68)
             }
+            TransactionType::Coinbase => 0,
         }
     }
 
-    pub fn process_block(&mut self, transactions: &[Transaction]) {
-        for tx in transactions {
-            self.nonce_tracker
-                .validate_and_update(&tx.data.sender, tx.data.nonce);
-
-            self.remove_transaction(&tx.tx_hash);
-        }
-
-        self.cleanup_invalid_nonces();
-    }
-
-    fn cleanup_invalid_nonces(&mut self) {
-        let mut to_remove = Vec::new();
-
-        for (hash, pool_tx) in &self.transactions {
-            let tx = &pool_tx.transaction;
-            let expected_nonce = self.nonce_tracker.get_nonce(&tx.data.sender) + 1;
-
-            if tx.data.nonce < expected_nonce {
-                to_remove.push(hash.clone());
-            }
-        }
+    fn remove_stale_transactions(&mut self, state: &ChainState) {
+        let stale: Vec<TxHash> = self
+            .transactions
+            .iter()
+            .filter(|(_, pool_tx)| pool_tx.transaction.data.nonce <= state.get_nonce(&pool_tx.transaction.data.sender))
+            .map(|(hash, _)| hash.clone())
+            .collect();
 
-        for hash in to_remove {
+        for hash in stale {
             self.remove_transaction(&hash);
         }
     }
@@ -268,3 +284,25 @@ impl TransactionPool {
         self.total_size
     }
 }
+
+impl ChainListener for TransactionPool {
+    fn on_chain_update(&mut self, disconnected: &[Block], connected: &[Block], state: &ChainState) {
+        let confirmed: HashSet<&TxHash> = connected
+            .iter()
+            .flat_map(|block| &block.transactions)
+            .map(|tx| &tx.tx_hash)
+            .collect();
+
+        for tx_hash in &confirmed {
+            self.remove_transaction(tx_hash);
+        }
+
+        for tx in disconnected.iter().flat_map(|block| &block.transactions) {
+            if !tx.is_coinbase() && !confirmed.contains(&tx.tx_hash) {
+                let _ = self.add_transaction(tx.clone());
+            }
+        }
+
+        self.remove_stale_transactions(state);
+    }
+}
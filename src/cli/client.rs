@@ -0,0 +1,54 @@
+use crate::constants::{API_PORT_OFFSET, REQUEST_TIMEOUT};
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper::header::CONTENT_TYPE;
+use hyper::{Method, Request};
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use serde_json::Value;
+use std::net::{IpAddr, SocketAddr};
+use tokio::time::timeout;
+
+pub struct ApiClient {
+    address: SocketAddr,
+    client: Client<HttpConnector, Full<Bytes>>,
+}
+
+impl ApiClient {
+    pub fn new(ip: IpAddr, node_port: u16) -> Result<Self, &'static str> {
+        let port = node_port.checked_add(API_PORT_OFFSET).ok_or("Port is too high for the HTTP API")?;
+        Ok(ApiClient {
+            address: SocketAddr::new(ip, port),
+            client: Client::builder(TokioExecutor::new()).build_http(),
+        })
+    }
+
+    pub async fn get(&self, path: &str) -> Result<Value, Box<dyn std::error::Error>> {
+        self.send(Method::GET, path, Bytes::new()).await
+    }
+
+    pub async fn post(&self, path: &str, body: &Value) -> Result<Value, Box<dyn std::error::Error>> {
+        self.send(Method::POST, path, Bytes::from(body.to_string())).await
+    }
+
+    async fn send(&self, method: Method, path: &str, body: Bytes) -> Result<Value, Box<dyn std::error::Error>> {
+        let request = Request::builder()
+            .method(method)
+            .uri(format!("http://{}{}", self.address, path))
+            .header(CONTENT_TYPE, "application/json")
+            .body(Full::new(body))?;
+
+        let response = timeout(REQUEST_TIMEOUT, self.client.request(request)).await??;
+        let status = response.status();
+        let bytes = response.into_body().collect().await?.to_bytes();
+        let value: Value = serde_json::from_slice(&bytes)?;
+
+        if !status.is_success() {
+            let message = value["error"].as_str().unwrap_or("request failed");
+            return Err(format!("{} ({})", message, status).into());
+        }
+
+        Ok(value)
+    }
+}
@@ -0,0 +1,251 @@
+use crate::auctions::auction_commands::{generate_auction_id, tx_bid, tx_create_auction, tx_end_auction, tx_start_auction};
+use crate::blockchain::transaction::Transaction;
+use crate::cli::client::ApiClient;
+use crate::kademlia::node::Node;
+use ed25519_dalek::Keypair;
+use serde_json::{json, Value};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+pub const USAGE: &str = "\
+Usage: ledger node run <SELF PORT> <BOOTSTRAP PORT>
+       ledger <SELF PORT> <BOOTSTRAP PORT>
+       ledger light <NODE PORT>
+       ledger --node <NODE PORT> [--json] chain info
+       ledger --node <NODE PORT> [--json] chain block <HEIGHT | HASH>
+       ledger --node <NODE PORT> [--json] chain tx <HASH>
+       ledger --node <NODE PORT> [--json] tx send <RECEIVER> <AMOUNT> [FEE]
+       ledger --node <NODE PORT> [--json] auction create <TITLE> <DESCRIPTION>
+       ledger --node <NODE PORT> [--json] auction start <ID>
+       ledger --node <NODE PORT> [--json] auction end <ID>
+       ledger --node <NODE PORT> [--json] auction bid <ID> <AMOUNT>
+       ledger --node <NODE PORT> [--json] auction list";
+
+struct Context {
+    client: ApiClient,
+    address: SocketAddr,
+    json: bool,
+}
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    let mut port = None;
+    let mut json = false;
+    let mut command = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--node" => port = Some(args.next().ok_or("Missing value for --node")?.parse::<u16>()?),
+            "--json" => json = true,
+            _ => command.push(arg.as_str()),
+        }
+    }
+
+    let port = port.ok_or("Missing --node <NODE PORT>")?;
+    let context = Context {
+        client: ApiClient::new(ip, port)?,
+        address: SocketAddr::new(ip, port),
+        json,
+    };
+
+    match command.as_slice() {
+        ["chain", "info"] => chain_info(&context).await,
+        ["chain", "block", id] => chain_block(&context, id).await,
+        ["chain", "tx", hash] => chain_tx(&context, hash).await,
+        ["tx", "send", receiver, amount] => tx_send(&context, receiver, amount.parse()?, 0).await,
+        ["tx", "send", receiver, amount, fee] => tx_send(&context, receiver, amount.parse()?, fee.parse()?).await,
+        ["auction", "create", title, description] => auction_create(&context, title, description).await,
+        ["auction", "start", id] => auction_start(&context, id).await,
+        ["auction", "end", id] => auction_end(&context, id).await,
+        ["auction", "bid", id, amount] => auction_bid(&context, id, amount.parse()?).await,
+        ["auction", "list"] => auction_list(&context).await,
+        _ => Err(USAGE.into()),
+    }
+}
+
+fn print_json(value: &Value) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn field(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn print_transaction(tx: &Value) {
+    println!("  Hash: {}", field(&tx["hash"]));
+    println!("  Type: {}", field(&tx["type"]));
+    println!("  Sender: {}", field(&tx["sender"]));
+    println!("  Receiver: {}", field(&tx["receiver"]));
+    println!("  Amount: {}", field(&tx["amount"]));
+    println!("  Fee: {}", field(&tx["fee"]));
+    println!("  Nonce: {}", field(&tx["nonce"]));
+    println!("  Data: {}", field(&tx["data"]));
+}
+
+async fn chain_info(context: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let info = context.client.get("/chain").await?;
+    if context.json {
+        return print_json(&info);
+    }
+
+    println!("[CHAIN]");
+    println!("  Chain ID: {}", field(&info["chain_id"]));
+    println!("  Height: {}", field(&info["height"]));
+    println!("  Tip: {}", field(&info["tip"]));
+    println!("  Cumulative Work: {}", field(&info["cumulative_work"]));
+    println!("  Next Bits: {}", field(&info["next_bits"]));
+    println!("  Finalized Height: {}", field(&info["finalized_height"]));
+    println!("  Pending Transactions: {}", field(&info["pool_size"]));
+    Ok(())
+}
+
+async fn chain_block(context: &Context, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let block = match id.parse::<usize>() {
+        Ok(height) => context.client.get(&format!("/blocks/{}", height)).await?,
+        Err(_) => context.client.get(&format!("/blocks/hash/{}", id)).await?,
+    };
+    if context.json {
+        return print_json(&block);
+    }
+
+    let transactions = block["transactions"].as_array().cloned().unwrap_or_default();
+
+    println!("[BLOCK {}]", field(&block["index"]));
+    println!("  Hash: {}", field(&block["hash"]));
+    println!("  Previous Hash: {}", field(&block["prev_hash"]));
+    println!("  Timestamp: {}", field(&block["timestamp"]));
+    println!("  Bits: {}", field(&block["bits"]));
+    println!("  Merkle Root: {}", field(&block["merkle_root"]));
+    println!("  State Root: {}", field(&block["state_root"]));
+    println!("  Transactions: {}", transactions.len());
+    for tx in &transactions {
+        println!("    {} {}", field(&tx["type"]), field(&tx["hash"]));
+    }
+    Ok(())
+}
+
+async fn chain_tx(context: &Context, hash: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let result = context.client.get(&format!("/transactions/{}", hash)).await?;
+    if context.json {
+        return print_json(&result);
+    }
+
+    println!("[TRANSACTION {}]", field(&result["status"]).to_uppercase());
+    if !result["height"].is_null() {
+        println!("  Block: {} (position {})", field(&result["height"]), field(&result["position"]));
+    }
+    print_transaction(&result["transaction"]);
+    Ok(())
+}
+
+async fn prepare(context: &Context) -> Result<(Keypair, String, u64), Box<dyn std::error::Error>> {
+    let keypair = Node::load_keypair(context.address)?;
+
+    let info = context.client.get("/chain").await?;
+    let chain_id = info["chain_id"].as_str().ok_or("Node didn't report its chain ID")?.to_string();
+
+    let account = context.client.get(&format!("/accounts/{}", hex::encode(keypair.public.to_bytes()))).await?;
+    let nonce = account["next_nonce"].as_u64().ok_or("Node didn't report the next nonce")?;
+
+    Ok((keypair, chain_id, nonce))
+}
+
+async fn submit(context: &Context, tx: Transaction, details: Value) -> Result<(), Box<dyn std::error::Error>> {
+    let response = context.client.post("/transactions", &json!({ "transaction": tx })).await?;
+
+    let mut result = details;
+    result["hash"] = response["hash"].clone();
+    result["nonce"] = json!(tx.data.nonce);
+    if context.json {
+        return print_json(&result);
+    }
+
+    println!("[TRANSACTION SUBMITTED]");
+    if let Some(details) = result.as_object() {
+        for (key, value) in details {
+            println!("  {}: {}", key, field(value));
+        }
+    }
+    Ok(())
+}
+
+async fn tx_send(context: &Context, receiver: &str, amount: u64, fee: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let receiver = hex::decode(receiver).map_err(|_| "Receiver must be a hex public key")?;
+    let (keypair, chain_id, nonce) = prepare(context).await?;
+
+    let tx = Transaction::create_transfer(&keypair, &chain_id, receiver.clone(), amount, nonce, fee)?;
+    submit(context, tx, json!({
+        "receiver": hex::encode(&receiver),
+        "amount": amount,
+        "fee": fee,
+    })).await
+}
+
+async fn auction_create(context: &Context, title: &str, description: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let (keypair, chain_id, nonce) = prepare(context).await?;
+
+    let auction_id = generate_auction_id(&keypair.public.to_bytes(), title, description, nonce);
+    let tx = tx_create_auction(&keypair, &chain_id, title.to_string(), description.to_string(), nonce)?;
+    submit(context, tx, json!({
+        "auction_id": auction_id,
+        "title": title,
+        "description": description,
+    })).await
+}
+
+async fn auction_start(context: &Context, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let (keypair, chain_id, nonce) = prepare(context).await?;
+
+    let tx = tx_start_auction(&keypair, &chain_id, id.to_string(), nonce)?;
+    submit(context, tx, json!({ "auction_id": id })).await
+}
+
+async fn auction_end(context: &Context, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let (keypair, chain_id, nonce) = prepare(context).await?;
+
+    let tx = tx_end_auction(&keypair, &chain_id, id.to_string(), nonce)?;
+    submit(context, tx, json!({ "auction_id": id })).await
+}
+
+async fn auction_bid(context: &Context, id: &str, amount: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let (keypair, chain_id, nonce) = prepare(context).await?;
+
+    let tx = tx_bid(&keypair, &chain_id, id.to_string(), amount, nonce)?;
+    submit(context, tx, json!({
+        "auction_id": id,
+        "amount": amount,
+    })).await
+}
+
+async fn auction_list(context: &Context) -> Result<(), Box<dyn std::error::Error>> {
+    let auctions = context.client.get("/auctions").await?;
+    if context.json {
+        return print_json(&auctions);
+    }
+
+    let auctions = auctions.as_array().cloned().unwrap_or_default();
+    if auctions.is_empty() {
+        println!("No auctions found in the blockchain.");
+        return Ok(());
+    }
+
+    for auction in &auctions {
+        let highest_bid = match auction["highest_bid"]["amount"].as_u64() {
+            Some(amount) => format!("highest bid: {}", amount),
+            None => "no bids".to_string(),
+        };
+        println!(
+            "[{}] {} - {} ({})",
+            field(&auction["status"]).to_uppercase(),
+            field(&auction["id"]),
+            field(&auction["title"]),
+            highest_bid,
+        );
+    }
+    Ok(())
+}
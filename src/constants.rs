@@ -1,23 +1,51 @@
 use std::time::Duration;
 
 pub const ALPHA: usize = 3;
+pub const API_PORT_OFFSET: u16 = 1000;
 pub const BLOCK_INTERVAL: Duration = Duration::from_secs(30);
+pub const BLOCK_SUBSIDY: u64 = 50_000;
+pub const CHECKPOINTS: &[(&str, u32, &str)] = &[];
 pub const CRYPTO_KEY_LENGTH: usize = 32;
+pub const DATA_DIR: &str = "data";
 pub const DIFFICULTY: usize = 2;
-pub const DIFFICULTY_PREFIX: &str = "0000";
+pub const FINALITY_DEPTH: usize = 100;
+pub const GENESIS_BITS: u32 = 0x1f00_ffff;
+pub const GENESIS_FILE: &str = "genesis.json";
+pub const GENESIS_TIMESTAMP: u128 = 1_735_689_600_000;
+pub const GOSSIP_RATE_LIMIT: u32 = 200;
+pub const GOSSIP_RATE_WINDOW: Duration = Duration::from_secs(1);
 pub const ID_LENGTH: usize = 20;
 pub const K: usize = 20;
 pub const KEY_LENGTH: usize = 20;
 pub const MAX_BLOCK_TIME: u128 = 600_000;
-pub const MAX_FORK_DEPTH: usize = 6;
+pub const MAX_BLOCKS_PER_REQUEST: usize = 100;
+pub const MAX_CACHED_TEMPLATES: usize = 32;
+pub const MAX_FUTURE_BLOCK_TIME: u128 = 7_200_000;
+pub const MAX_FUTURE_TX_TIME: u128 = 3_600_000;
+pub const MAX_HEADERS_PER_REQUEST: u32 = 2000;
 pub const MAX_MINING_TIME: Duration = Duration::from_secs(300);
 pub const MAX_NODES_TO_SYNC: usize = 3;
+pub const MAX_ORPHAN_BLOCKS: usize = 100;
 pub const MAX_POOL_SIZE: usize = 10000;
+pub const MAX_RATE_LIMITED_PEERS: usize = 1024;
+pub const MAX_RETARGET_FACTOR: u64 = 4;
+pub const MAX_SEEN_TRANSACTIONS: usize = 50000;
+pub const MAX_STATE_SNAPSHOTS: usize = 10;
 pub const MAX_TRANSACTIONS_PER_BLOCK: usize = 10;
+pub const MAX_TRANSACTIONS_PER_REQUEST: usize = 1000;
 pub const MAX_TXS_PER_SENDER: usize = 50;
+pub const MAX_TX_AGE: u128 = 86_400_000;
+pub const MINER_CHECK_INTERVAL: u64 = 4096;
 pub const MIN_BLOCK_TIME: u128 = 1_000;
-pub const MIN_FEE_RATE: u64 = 0;
 pub const N_BUCKETS: usize = 160;
+pub const POW_LIMIT_BITS: u32 = 0x200f_ffff;
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+pub const RETARGET_INTERVAL: u32 = 10;
+pub const STATE_SNAPSHOT_INTERVAL: usize = 100;
 pub const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+pub const SYNC_RATE_LIMIT: u32 = 50;
+pub const SYNC_RATE_WINDOW: Duration = Duration::from_secs(1);
+pub const TEMPLATE_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
 pub const TIMEOUT: u64 = 500;
 pub const TRIES: usize = 3;
+pub const TX_VALIDITY_WINDOW: u128 = 3_600_000;
@@ -1,8 +1,15 @@
-use crate::blockchain::block::Block;
-use crate::blockchain::blockchain::Blockchain;
-use crate::blockchain::transaction::{Transaction, TransactionType};
+use crate::blockchain::block::{Block, BlockHeader};
+use crate::blockchain::blockchain::{Blockchain, LightClient};
+use crate::blockchain::encoding::{Decode, Encode};
+use crate::blockchain::lib::BHash;
+use crate::blockchain::miner::{Miner, MiningResult};
+use crate::blockchain::template::BlockTemplate;
+use crate::blockchain::Hashable;
+use crate::blockchain::storage::FileBlockStore;
+use crate::blockchain::target::U256;
+use crate::blockchain::transaction::{Transaction, TransactionType, TxHash};
 use crate::blockchain::transaction_pool::TransactionPool;
-use crate::constants::{ALPHA, BLOCK_INTERVAL, CRYPTO_KEY_LENGTH, ID_LENGTH, K, KEY_LENGTH, MAX_NODES_TO_SYNC, MAX_TRANSACTIONS_PER_BLOCK, SYNC_INTERVAL, TIMEOUT, TRIES};
+use crate::constants::{ALPHA, BLOCK_INTERVAL, CRYPTO_KEY_LENGTH, DATA_DIR, ID_LENGTH, K, KEY_LENGTH, MAX_BLOCKS_PER_REQUEST, MAX_CACHED_TEMPLATES, MAX_HEADERS_PER_REQUEST, MAX_NODES_TO_SYNC, MAX_TRANSACTIONS_PER_BLOCK, MAX_TRANSACTIONS_PER_REQUEST, REQUEST_TIMEOUT, SYNC_INTERVAL, TIMEOUT, TRIES, TX_VALIDITY_WINDOW};
 use crate::kademlia::kademlia_proto::kademlia_client::KademliaClient;
 use crate::kademlia::kademlia_proto::kademlia_server::KademliaServer;
 use crate::kademlia::kademlia_proto::{
@@ -10,6 +17,18 @@ use crate::kademlia::kademlia_proto::{
 };
 use crate::kademlia::routing_table::RoutingTable;
 use crate::kademlia::service::KademliaService;
+use crate::ledger::ledger_proto::ledger_client::LedgerClient;
+use crate::ledger::ledger_proto::ledger_server::LedgerServer;
+use crate::ledger::rate_limiter::RateLimiter;
+use crate::ledger::ledger_proto::{
+    AnnounceBlockRequest, AnnounceInventoryRequest, AnnounceTransactionRequest, GetBlocksRequest, GetHeadersRequest,
+    GetMempoolRequest, GetTransactionsRequest, SubscribeTipRequest, Tip,
+};
+use crate::ledger::service::LedgerService;
+use crate::light::light_proto::light_server::LightServer;
+use crate::light::service::LightService;
+use crate::mining::mining_proto::mining_server::MiningServer;
+use crate::mining::service::MiningService;
 use ed25519_dalek::{Keypair, PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
 use futures::stream::{FuturesUnordered, StreamExt};
 use rand::rngs::OsRng;
@@ -18,9 +37,11 @@ use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::net::SocketAddr;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 use std::{fmt, fs};
+use tokio::sync::watch;
 use tokio::time::{interval, timeout};
 use tonic::transport::Server;
 use tonic::{Request, Status};
@@ -33,9 +54,35 @@ pub struct Node {
     address: SocketAddr,
     routing_table: Arc<RwLock<RoutingTable>>,
     storage: Arc<RwLock<HashMap<[u8; KEY_LENGTH], Vec<u8>>>>,
+    chain: Option<Arc<ChainContext>>,
+}
+
+struct ChainContext {
     blockchain: Arc<RwLock<Blockchain>>,
     transaction_pool: Arc<Mutex<TransactionPool>>,
+    tip: Arc<watch::Sender<Tip>>,
     is_mining: Arc<RwLock<bool>>,
+    mining_job: Arc<Mutex<Option<MiningJob>>>,
+    templates: Arc<Mutex<HashMap<BHash, BlockTemplate>>>,
+}
+
+struct MiningJob {
+    prev_hash: BHash,
+    tx_count: usize,
+    min_fee: Option<u64>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl MiningJob {
+    fn new(template: &BlockTemplate, cancel: Arc<AtomicBool>) -> Self {
+        let transactions = &template.get_block().transactions;
+        MiningJob {
+            prev_hash: template.get_prev_hash().to_vec(),
+            tx_count: transactions.len().saturating_sub(1),
+            min_fee: transactions.iter().skip(1).map(|tx| tx.data.fee).min(),
+            cancel,
+        }
+    }
 }
 
 impl fmt::Display for Node {
@@ -44,17 +91,6 @@ impl fmt::Display for Node {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-pub enum BlockchainMessage {
-    RequestFullBlockchain,
-    ResponseFullBlockchain { blockchain: Blockchain },
-    ResponseBlocks { blocks: Vec<Block> },
-    NewBlock { block: Block },
-    NewTransaction { transaction: Transaction },
-    RequestTransactionPool,
-    ResponseTransactionPool { transactions: Vec<Transaction> },
-}
-
 #[derive(Serialize, Deserialize)]
 struct StoredKeyData {
     public_key: [u8; CRYPTO_KEY_LENGTH],
@@ -76,9 +112,7 @@ impl Node {
             address,
             routing_table: Arc::new(RwLock::new(RoutingTable::new(id))),
             storage: Arc::new(RwLock::new(HashMap::new())),
-            blockchain: Arc::new(RwLock::new(Blockchain::new())),
-            transaction_pool: Arc::new(Mutex::new(TransactionPool::new())),
-            is_mining: Arc::new(RwLock::new(false)),
+            chain: Some(Arc::new(Self::create_chain_context(Self::load_blockchain(address)))),
         }
     }
 
@@ -92,15 +126,70 @@ impl Node {
             address,
             routing_table: Arc::new(RwLock::new(RoutingTable::new(id))),
             storage: Arc::new(RwLock::new(HashMap::new())),
-            blockchain: Arc::new(RwLock::new(Blockchain::new())),
-            transaction_pool: Arc::new(Mutex::new(TransactionPool::new())),
+            chain: Some(Arc::new(Self::create_chain_context(Self::load_blockchain(address)))),
+        }
+    }
+
+    pub fn new_peer(address: SocketAddr) -> Self {
+        let (public_key, private_key) = Self::get_or_create_keypair(address);
+        let hash = Sha256::digest(public_key);
+        let id = hash[..ID_LENGTH]
+            .try_into()
+            .expect("SHA-256 hash length must be 160 bits (20 bytes)");
+
+        Self {
+            public_key,
+            private_key,
+            id,
+            address,
+            routing_table: Arc::new(RwLock::new(RoutingTable::new(id))),
+            storage: Arc::new(RwLock::new(HashMap::new())),
+            chain: None,
+        }
+    }
+
+    fn create_chain_context(mut blockchain: Blockchain) -> ChainContext {
+        let transaction_pool = Arc::new(Mutex::new(TransactionPool::new(blockchain.get_chain_id().to_string())));
+        blockchain.add_listener(transaction_pool.clone());
+
+        ChainContext {
+            transaction_pool,
+            tip: Arc::new(watch::Sender::new(Self::chain_tip(&blockchain))),
+            blockchain: Arc::new(RwLock::new(blockchain)),
             is_mining: Arc::new(RwLock::new(false)),
+            mining_job: Arc::new(Mutex::new(None)),
+            templates: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    fn get_or_create_keypair(address: SocketAddr) -> ([u8; CRYPTO_KEY_LENGTH], [u8; CRYPTO_KEY_LENGTH]) {
+    fn chain(&self) -> Result<&ChainContext, &'static str> {
+        self.chain.as_deref().ok_or("Node doesn't hold a local blockchain")
+    }
+
+    fn load_blockchain(address: SocketAddr) -> Blockchain {
+        let ip_str = address.ip().to_string().replace(':', "_");
+        let data_dir = format!("{}/{}_{}", DATA_DIR, ip_str, address.port());
+
+        let loaded = FileBlockStore::open(&data_dir)
+            .map_err(|_| "Failed to open block store")
+            .and_then(Blockchain::with_store);
+
+        match loaded {
+            Ok(blockchain) => blockchain,
+            Err(e) => {
+                println!("Not persisting blocks, stored blockchain in {} is unusable: {}", data_dir, e);
+                Blockchain::new()
+            }
+        }
+    }
+
+    fn key_file_path(address: SocketAddr) -> String {
         let ip_str = address.ip().to_string().replace(":", "_");
-        let key_file_path = format!("keys/{}_{}.json", ip_str, address.port());
+        format!("keys/{}_{}.json", ip_str, address.port())
+    }
+
+    fn get_or_create_keypair(address: SocketAddr) -> ([u8; CRYPTO_KEY_LENGTH], [u8; CRYPTO_KEY_LENGTH]) {
+        let key_file_path = Self::key_file_path(address);
 
         if let Ok(existing_keys) = Self::load_keypair_from_file(&key_file_path) {
             return existing_keys;
@@ -141,6 +230,13 @@ impl Node {
         Ok(())
     }
 
+    pub fn load_keypair(address: SocketAddr) -> Result<Keypair, Box<dyn std::error::Error>> {
+        let (public_key, private_key) = Self::load_keypair_from_file(&Self::key_file_path(address))?;
+        let secret = DalekSecretKey::from_bytes(&private_key).map_err(|_| "Invalid private key")?;
+        let public = DalekPublicKey::from_bytes(&public_key).map_err(|_| "Invalid public key")?;
+        Ok(Keypair { secret, public })
+    }
+
     pub fn get_keypair(&self) -> Result<Keypair, &'static str> {
         let secret =
             DalekSecretKey::from_bytes(&self.private_key).map_err(|_| "Invalid private key")?;
@@ -169,12 +265,48 @@ impl Node {
         self.storage.clone()
     }
 
-    pub fn get_blockchain(&self) -> Arc<RwLock<Blockchain>> {
-        self.blockchain.clone()
+    pub fn get_blockchain(&self) -> Result<Arc<RwLock<Blockchain>>, &'static str> {
+        Ok(self.chain()?.blockchain.clone())
+    }
+
+    pub fn get_transaction_pool(&self) -> Result<Arc<Mutex<TransactionPool>>, &'static str> {
+        Ok(self.chain()?.transaction_pool.clone())
+    }
+
+    pub fn subscribe_tip(&self) -> Result<watch::Receiver<Tip>, &'static str> {
+        Ok(self.chain()?.tip.subscribe())
+    }
+
+    fn chain_tip(blockchain: &Blockchain) -> Tip {
+        Tip {
+            height: blockchain.get_block_height() as u32,
+            hash: blockchain.get_last_block().map(|block| block.hash.clone()).unwrap_or_default(),
+            work: blockchain.get_cumulative_work().to_be_bytes().to_vec(),
+        }
+    }
+
+    fn notify_tip(&self) {
+        let Ok(chain) = self.chain() else {
+            return;
+        };
+
+        let tip = {
+            let blockchain = chain.blockchain.read().unwrap();
+            Self::chain_tip(&blockchain)
+        };
+
+        chain.tip.send_if_modified(|current| {
+            if *current == tip {
+                return false;
+            }
+            *current = tip;
+            true
+        });
     }
 
-    pub fn get_transaction_pool(&self) -> Arc<Mutex<TransactionPool>> {
-        self.transaction_pool.clone()
+    pub fn get_chain_id(&self) -> Result<String, &'static str> {
+        let blockchain = self.chain()?.blockchain.read().unwrap();
+        Ok(blockchain.get_chain_id().to_string())
     }
 
     pub async fn create_transaction(
@@ -184,16 +316,18 @@ impl Node {
         amount: Option<u64>,
         data: Option<String>,
     ) -> Result<Transaction, &'static str> {
-        let blockchain = self.blockchain.read().unwrap();
+        let blockchain = self.chain()?.blockchain.read().unwrap();
         let sender = self.public_key.to_vec();
         let nonce = blockchain.get_next_nonce(&sender);
 
         let fee = match tx_type {
             TransactionType::Transfer => 1000,
             TransactionType::Data => 500,
+            TransactionType::Coinbase => return Err("Coinbase transactions are created by miners"),
         };
 
         let tx_data = crate::blockchain::transaction::TransactionData {
+            chain_id: blockchain.get_chain_id().to_string(),
             sender: sender.clone(),
             receiver,
             timestamp: crate::blockchain::lib::now(),
@@ -202,7 +336,7 @@ impl Node {
             data,
             nonce,
             fee,
-            valid_until: Some(crate::blockchain::lib::now() + 3_600_000),
+            valid_until: Some(crate::blockchain::lib::now() + TX_VALIDITY_WINDOW),
         };
 
         let keypair = self.get_keypair()?;
@@ -211,21 +345,107 @@ impl Node {
     }
 
     pub async fn submit_transaction(&self, tx: Transaction) -> Result<(), &'static str> {
-        if !tx.verify() {
+        self.accept_transaction(tx.clone())?;
+
+        let node = self.clone();
+        tokio::spawn(async move {
+            node.relay_transaction(tx).await;
+        });
+
+        Ok(())
+    }
+
+    fn accept_transaction(&self, tx: Transaction) -> Result<(), &'static str> {
+        let chain_id = self.get_chain_id()?;
+        if tx.data.chain_id != chain_id {
+            return Err("Transaction belongs to a different chain");
+        }
+
+        if !tx.verify(&chain_id) {
             return Err("Invalid transaction signature");
         }
 
+        if tx.tx_hash != tx.hash() {
+            return Err("Transaction hash doesn't match its contents");
+        }
+
         {
-            let mut pool = self.transaction_pool.lock().unwrap();
+            let mut pool = self.chain()?.transaction_pool.lock().unwrap();
+            if pool.has_seen(&tx.tx_hash) {
+                return Err("Transaction already seen");
+            }
             pool.add_transaction(tx.clone())?;
         }
 
+        self.interrupt_mining_for_transaction(&tx);
+
+        Ok(())
+    }
+
+    async fn relay_transaction(&self, tx: Transaction) {
+        let nodes = {
+            let routing_table = self.routing_table.read().unwrap();
+            routing_table.find_closest_nodes(self.get_id(), K)
+        };
+
+        let mut relay_futures = FuturesUnordered::new();
+
+        for node in nodes {
+            if node.get_id() != self.get_id() {
+                let tx = &tx;
+                relay_futures.push(async move {
+                    let _ = timeout(
+                        Duration::from_secs(5),
+                        self.relay_transaction_to(&node, tx),
+                    )
+                        .await;
+                });
+            }
+        }
+
+        while relay_futures.next().await.is_some() {}
+    }
+
+    async fn relay_transaction_to(&self, target: &Node, tx: &Transaction) -> Result<(), Box<dyn std::error::Error>> {
+        let wanted = self.announce_inventory(target, vec![tx.tx_hash.clone()]).await?;
+        if wanted.contains(&tx.tx_hash) {
+            self.announce_transaction(target, tx).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn sync_transaction_pool(&self) {
+        let nodes = {
+            let routing_table = self.routing_table.read().unwrap();
+            routing_table.find_closest_nodes(self.get_id(), K)
+        };
+
+        for node in nodes.iter().filter(|node| node.get_id() != self.get_id()).take(MAX_NODES_TO_SYNC) {
+            let _ = timeout(REQUEST_TIMEOUT, self.sync_transaction_pool_from(node)).await;
+        }
+    }
+
+    async fn sync_transaction_pool_from(&self, node: &Node) -> Result<(), Box<dyn std::error::Error>> {
+        let inventory = self.get_mempool(node).await?;
+
+        let wanted: Vec<TxHash> = {
+            let pool = self.chain()?.transaction_pool.lock().unwrap();
+            inventory.into_iter().filter(|tx_hash| !pool.has_seen(tx_hash)).collect()
+        };
+
+        for batch in wanted.chunks(MAX_TRANSACTIONS_PER_REQUEST) {
+            for tx in self.get_transactions(node, batch.to_vec()).await? {
+                let _ = self.accept_transaction(tx);
+            }
+        }
+
         Ok(())
     }
 
     pub async fn mine_block(&self) -> Result<Block, &'static str> {
         {
-            let mut mining = self.is_mining.write().unwrap();
+            let mut mining = self.chain()?.is_mining.write().unwrap();
             if *mining {
                 return Err("Already Mining");
             }
@@ -235,7 +455,7 @@ impl Node {
         let result = self.mine_pow_block().await;
 
         {
-            let mut mining = self.is_mining.write().unwrap();
+            let mut mining = self.chain()?.is_mining.write().unwrap();
             *mining = false;
         }
 
@@ -243,42 +463,141 @@ impl Node {
     }
 
     async fn mine_pow_block(&self) -> Result<Block, &'static str> {
+        loop {
+            let mut template = self.create_template()?;
+
+            let cancel = Arc::new(AtomicBool::new(false));
+            {
+                let mut job = self.chain()?.mining_job.lock().unwrap();
+                *job = Some(MiningJob::new(&template, cancel.clone()));
+            }
+
+            if !self.is_chain_tip(template.get_prev_hash()) {
+                continue;
+            }
+
+            let result = tokio::task::spawn_blocking(move || Miner::default().mine(&mut template, &cancel))
+                .await
+                .map_err(|_| "Mining task failed");
+
+            {
+                let mut job = self.chain()?.mining_job.lock().unwrap();
+                *job = None;
+            }
+
+            let block = match result? {
+                MiningResult::Found(block) => block,
+                MiningResult::Cancelled => continue,
+                MiningResult::TimedOut => return Err("Mining timed out - adjust difficulty"),
+            };
+
+            match self.accept_mined_block(block.clone()).await {
+                Ok(()) => return Ok(block),
+                Err(e) if self.is_chain_tip(&block.prev_hash) => return Err(e),
+                Err(_) => continue,
+            }
+        }
+    }
+
+    fn create_template(&self) -> Result<BlockTemplate, &'static str> {
+        let keypair = self.get_keypair()?;
+
+        let blockchain = self.chain()?.blockchain.read().unwrap();
         let transactions = {
-            let pool = self.transaction_pool.lock().unwrap();
-            pool.get_transactions_4_block(MAX_TRANSACTIONS_PER_BLOCK)
+            let pool = self.chain()?.transaction_pool.lock().unwrap();
+            pool.get_transactions_4_block(blockchain.get_state(), MAX_TRANSACTIONS_PER_BLOCK)
         };
 
-        let mut block = {
-            let blockchain = self.blockchain.read().unwrap();
-            blockchain.create_block(transactions)?
-        };
+        blockchain.create_template(&keypair, transactions)
+    }
 
-        {
-            let blockchain = self.blockchain.read().unwrap();
-            blockchain.mine_block(&mut block)?;
+    pub fn get_block_template(&self) -> Result<BlockTemplate, &'static str> {
+        let template = self.create_template()?;
+
+        let mut templates = self.chain()?.templates.lock().unwrap();
+        templates.retain(|_, cached| cached.get_prev_hash() == template.get_prev_hash());
+        if templates.len() >= MAX_CACHED_TEMPLATES {
+            templates.clear();
         }
+        templates.insert(template.get_id(), template.clone());
+
+        Ok(template)
+    }
+
+    pub async fn submit_block_solution(
+        &self,
+        template_id: &[u8],
+        extra_nonce: u64,
+        timestamp: u128,
+        nonce: u64,
+    ) -> Result<Block, &'static str> {
+        let block = {
+            let templates = self.chain()?.templates.lock().unwrap();
+            let template = templates.get(template_id).ok_or("Unknown or stale block template")?;
+            template.solve(extra_nonce, timestamp, nonce)?
+        };
 
+        self.accept_mined_block(block.clone()).await?;
+
+        Ok(block)
+    }
+
+    async fn accept_mined_block(&self, block: Block) -> Result<(), &'static str> {
         {
-            let mut blockchain = self.blockchain.write().unwrap();
+            let mut blockchain = self.chain()?.blockchain.write().unwrap();
             blockchain.add_block(block.clone())?;
         }
 
-        {
-            let mut pool = self.transaction_pool.lock().unwrap();
-            pool.process_block(&block.transactions);
-        }
+        self.interrupt_stale_mining();
+        self.notify_tip();
+        self.broadcast_block(block).await;
 
-        self.broadcast_block(block.clone()).await;
+        Ok(())
+    }
 
-        Ok(block)
+    fn is_chain_tip(&self, hash: &[u8]) -> bool {
+        let Ok(chain) = self.chain() else {
+            return false;
+        };
+
+        let blockchain = chain.blockchain.read().unwrap();
+        blockchain.get_last_block().is_some_and(|block| block.hash == hash)
+    }
+
+    fn interrupt_stale_mining(&self) {
+        let Ok(chain) = self.chain() else {
+            return;
+        };
+
+        let tip = {
+            let blockchain = chain.blockchain.read().unwrap();
+            blockchain.get_last_block().map(|block| block.hash.clone())
+        };
+
+        let job = chain.mining_job.lock().unwrap();
+        if let Some(job) = job.as_ref() {
+            if tip.as_ref() != Some(&job.prev_hash) {
+                job.cancel.store(true, Ordering::Relaxed);
+            }
+        }
     }
 
-    async fn broadcast_block(&self, block: Block) {
-        let message = BlockchainMessage::NewBlock {
-            block: block.clone(),
+    fn interrupt_mining_for_transaction(&self, tx: &Transaction) {
+        let Ok(chain) = self.chain() else {
+            return;
         };
-        let data = serde_json::to_vec(&message).unwrap_or_default();
 
+        let job = chain.mining_job.lock().unwrap();
+        if let Some(job) = job.as_ref() {
+            let has_room = job.tx_count < MAX_TRANSACTIONS_PER_BLOCK;
+            let pays_more = job.min_fee.is_some_and(|fee| tx.data.fee > fee);
+            if has_room || pays_more {
+                job.cancel.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub async fn broadcast_block(&self, block: Block) {
         let nodes = {
             let routing_table = self.routing_table.read().unwrap();
             routing_table.find_closest_nodes(self.get_id(), K)
@@ -288,28 +607,28 @@ impl Node {
 
         for node in nodes {
             if node.get_id() != self.get_id() {
-                let data_clone = data.clone();
-                let key = block.hash[..KEY_LENGTH]
-                    .try_into()
-                    .unwrap_or([0; KEY_LENGTH]);
-
+                let block = &block;
                 broadcast_futures.push(async move {
                     let _ = timeout(
                         Duration::from_secs(5),
-                        self.store_at(&node, key, data_clone),
+                        self.announce_block(&node, block),
                     )
                         .await;
                 });
             }
         }
 
-        while let Some(_) = broadcast_futures.next().await {}
+        while broadcast_futures.next().await.is_some() {}
     }
 
     pub async fn sync_blockchain(&self) {
-        let current_height = {
-            let blockchain = self.blockchain.read().unwrap();
-            blockchain.get_block_height()
+        let Ok(chain) = self.chain() else {
+            return;
+        };
+
+        let current_work = {
+            let blockchain = chain.blockchain.read().unwrap();
+            blockchain.get_cumulative_work()
         };
 
         let nodes = {
@@ -321,175 +640,159 @@ impl Node {
             return;
         }
 
-        let mut sync_futures = FuturesUnordered::new();
+        let mut tip_futures = FuturesUnordered::new();
 
         for node in nodes.iter().take(MAX_NODES_TO_SYNC) {
             if node.get_id() != self.get_id() {
-                sync_futures.push(self.request_full_blockchain(node.clone()));
+                let node = node.clone();
+                tip_futures.push(async move {
+                    let tip = timeout(REQUEST_TIMEOUT, self.get_tip(&node)).await;
+                    (node, tip)
+                });
             }
         }
 
-        let mut best_blockchain: Option<Blockchain> = None;
-        let mut best_height = current_height;
+        let mut best_node = None;
+        let mut best_work = current_work;
 
-        while let Some(result) = sync_futures.next().await {
-            if let Ok(blockchain) = result {
-                let height = blockchain.get_block_height();
-
-                if blockchain.is_chain_valid(None) {
-                    if height > best_height {
-                        best_height = height;
-                        best_blockchain = Some(blockchain);
-                    } else if height == best_height && best_blockchain.is_none() {
-                        best_blockchain = Some(blockchain);
-                    }
+        while let Some((node, tip)) = tip_futures.next().await {
+            if let Some(work) = tip.ok().and_then(|tip| tip.ok()).and_then(|tip| U256::from_be_bytes(&tip.work)) {
+                if work > best_work {
+                    best_work = work;
+                    best_node = Some(node);
                 }
             }
         }
 
-        if let Some(blockchain) = best_blockchain {
-            let mut current_blockchain = self.blockchain.write().unwrap();
-            *current_blockchain = blockchain;
-
-            let mut pool = self.transaction_pool.lock().unwrap();
-            pool.clear();
+        if let Some(node) = best_node {
+            let _ = self.sync_from(&node).await;
+            self.interrupt_stale_mining();
+            self.notify_tip();
         }
     }
 
-    async fn request_full_blockchain(
-        &self,
-        node: Node,
-    ) -> Result<Blockchain, Box<dyn std::error::Error>> {
-        let request_key = {
-            let mut hasher = Sha256::new();
-            hasher.update(b"blockchain_request_v2");
-            hasher.update(&self.id);
-            hasher.update(&node.get_id());
-            hasher.update(&crate::blockchain::lib::now().to_be_bytes());
-            hasher.update(&rand::random::<[u8; 8]>());
-            let hash = hasher.finalize();
-            hash[..KEY_LENGTH].try_into().unwrap_or([0; KEY_LENGTH])
+    async fn sync_from(&self, node: &Node) -> Result<(), Box<dyn std::error::Error>> {
+        let mut locator = {
+            let blockchain = self.chain()?.blockchain.read().unwrap();
+            blockchain.get_locator()
         };
 
-        let response_key = {
-            let mut hasher = Sha256::new();
-            hasher.update(b"blockchain_response_v2");
-            hasher.update(&request_key);
-            let hash = hasher.finalize();
-            hash[..KEY_LENGTH].try_into().unwrap_or([0; KEY_LENGTH])
-        };
-
-        let request_with_response_key = format!("REQUEST:{}", hex::encode(&response_key));
-        self.store_at(&node, request_key, request_with_response_key.into_bytes())
-            .await?;
+        let mut headers: Option<LightClient> = None;
+        let mut fork_point = 0;
 
-        tokio::time::sleep(Duration::from_millis(3000)).await;
-
-        for _ in 1..=3 {
-            match self.find_value(node.clone(), response_key).await {
-                Ok((Some(data), _)) => {
-                    match serde_json::from_slice::<BlockchainMessage>(&data) {
-                        Ok(message) => match message {
-                            BlockchainMessage::ResponseFullBlockchain { blockchain } => {
-                                return Ok(blockchain);
-                            }
-                            _ => {}
-                        },
-                        Err(_) => {}
-                    }
-                }
-                _ => {}
-            }
-
-            tokio::time::sleep(Duration::from_millis(1000)).await;
-        }
-
-        Err("Failed to receive valid blockchain response after 3 attempts".into())
-    }
-
-    pub async fn handle_blockchain_message(&self, data: &[u8]) -> Option<Vec<u8>> {
-        if let Ok(text) = std::str::from_utf8(data) {
-            if text.starts_with("REQUEST:") {
-                let response_key_hex = &text[8..];
-                if let Ok(response_key_bytes) = hex::decode(response_key_hex) {
-                    if response_key_bytes.len() == KEY_LENGTH {
-                        let blockchain = self.blockchain.read().unwrap().clone();
-
-                        let safe_blockchain = Blockchain {
-                            blocks: blockchain.blocks.clone(),
-                            difficulty: blockchain.difficulty,
-                            forks: HashMap::new(),
-                            balances: HashMap::new(),
-                        };
-
-                        let response = BlockchainMessage::ResponseFullBlockchain {
-                            blockchain: safe_blockchain,
-                        };
-
-                        match serde_json::to_vec(&response) {
-                            Ok(response_data) => {
-                                let response_key: [u8; KEY_LENGTH] =
-                                    response_key_bytes.try_into().unwrap();
-                                tokio::spawn({
-                                    let node = self.clone();
-                                    async move {
-                                        let storage = node.get_storage();
-                                        let mut storage_guard = storage.write().unwrap();
-                                        storage_guard.insert(response_key, response_data);
-                                    }
-                                });
-
-                                return Some(b"OK".to_vec());
-                            }
-                            Err(_) => {}
-                        }
-                    }
+        loop {
+            let batch = timeout(
+                REQUEST_TIMEOUT,
+                self.get_headers(node, locator.clone(), MAX_HEADERS_PER_REQUEST),
+            )
+                .await??;
+
+            let Some(first) = batch.first() else {
+                break;
+            };
+
+            let chain = match headers.as_mut() {
+                Some(chain) => chain,
+                None => {
+                    let blockchain = self.chain()?.blockchain.read().unwrap();
+                    fork_point = blockchain
+                        .blocks
+                        .iter()
+                        .position(|block| block.hash == first.prev_hash)
+                        .ok_or("Peer headers don't connect to our chain")?;
+                    headers.insert(LightClient::from_headers(blockchain.get_headers_range(0, fork_point + 1)))
                 }
+            };
+
+            let batch_size = batch.len();
+            for header in batch {
+                locator = vec![header.hash.clone()];
+                chain.add_header(header)?;
+            }
+
+            if batch_size < MAX_HEADERS_PER_REQUEST as usize {
+                break;
             }
         }
 
-        if let Ok(message) = serde_json::from_slice::<BlockchainMessage>(data) {
-            match message {
-                BlockchainMessage::RequestFullBlockchain => {
-                    let blockchain = self.blockchain.read().unwrap().clone();
+        let Some(headers) = headers else {
+            return Ok(());
+        };
 
-                    let safe_blockchain = Blockchain {
-                        blocks: blockchain.blocks.clone(),
-                        difficulty: blockchain.difficulty,
-                        forks: HashMap::new(),
-                        balances: HashMap::new(),
-                    };
+        {
+            let blockchain = self.chain()?.blockchain.read().unwrap();
+            if headers.get_cumulative_work() <= blockchain.get_cumulative_work() {
+                return Err("Peer chain doesn't have more work than ours".into());
+            }
+        }
 
-                    let response = BlockchainMessage::ResponseFullBlockchain {
-                        blockchain: safe_blockchain,
-                    };
+        let mut next = fork_point + 1;
+        while next < headers.get_height() {
+            let blocks = timeout(
+                REQUEST_TIMEOUT,
+                self.get_blocks(node, next, MAX_BLOCKS_PER_REQUEST),
+            )
+                .await??;
 
-                    match serde_json::to_vec(&response) {
-                        Ok(response_data) => {
-                            return Some(response_data);
-                        }
-                        Err(_) => {}
-                    }
+            if blocks.is_empty() {
+                return Err("Peer has no blocks for the announced headers".into());
+            }
+
+            for block in blocks {
+                let header = headers.get_header(next).ok_or("Peer sent more blocks than headers")?;
+                if block.hash != header.hash {
+                    return Err("Block doesn't match its header".into());
                 }
 
-                BlockchainMessage::NewBlock { block } => {
-                    let _ = self.receive_new_block(block).await;
+                let mut blockchain = self.chain()?.blockchain.write().unwrap();
+                if !blockchain.contains_block(&block.hash) && !blockchain.is_orphan(&block.hash) {
+                    blockchain.receive_block(block)?;
                 }
-                _ => {}
+                next += 1;
             }
         }
 
-        None
+        Ok(())
+    }
+
+    pub async fn receive_announced_block(&self, block: Block) -> Result<bool, &'static str> {
+        let has_parent = {
+            let blockchain = self.chain()?.blockchain.read().unwrap();
+            if blockchain.contains_block(&block.hash) || blockchain.is_orphan(&block.hash) {
+                return Ok(false);
+            }
+            blockchain.contains_block(&block.prev_hash)
+        };
+
+        if !has_parent {
+            let node = self.clone();
+            tokio::spawn(async move {
+                node.sync_blockchain().await;
+            });
+        }
+
+        self.receive_new_block(block.clone()).await?;
+
+        let node = self.clone();
+        tokio::spawn(async move {
+            node.broadcast_block(block).await;
+        });
+
+        Ok(true)
     }
 
     async fn receive_new_block(&self, block: Block) -> Result<(), &'static str> {
         println!("\n\nReceived block {}", block.index);
 
-        let mut blockchain = self.blockchain.write().unwrap();
-        match blockchain.receive_block(block.clone()) {
+        let result = {
+            let mut blockchain = self.chain()?.blockchain.write().unwrap();
+            blockchain.receive_block(block.clone())
+        };
+
+        match result {
             Ok(_) => {
-                let mut pool = self.transaction_pool.lock().unwrap();
-                pool.process_block(&block.transactions);
+                self.interrupt_stale_mining();
+                self.notify_tip();
                 println!("Successfully added block {} to blockchain\n", block.index);
                 Ok(())
             }
@@ -507,7 +810,7 @@ impl Node {
             loop {
                 interval.tick().await;
 
-                if !*node.is_mining.read().unwrap() {
+                if node.chain().is_ok_and(|chain| !*chain.is_mining.read().unwrap()) {
                     let _ = node.mine_block().await;
                 }
             }
@@ -525,11 +828,11 @@ impl Node {
         });
     }
 
-    pub fn get_blockchain_info(&self) -> (usize, Option<String>) {
-        let blockchain = self.blockchain.read().unwrap();
+    pub fn get_blockchain_info(&self) -> Result<(usize, Option<String>), &'static str> {
+        let blockchain = self.chain()?.blockchain.read().unwrap();
         let height = blockchain.get_block_height();
         let last_hash = blockchain.get_last_block().map(|b| hex::encode(&b.hash));
-        (height, last_hash)
+        Ok((height, last_hash))
     }
 
     pub fn from_sender(sender: &ProtoNode) -> Option<Self> {
@@ -542,9 +845,7 @@ impl Node {
             address: SocketAddr::new(sender.ip.parse().ok()?, sender.port as u16),
             routing_table: Arc::new(RwLock::new(RoutingTable::new(id))),
             storage: Arc::new(Default::default()),
-            blockchain: Arc::new(RwLock::new(Blockchain::new())),
-            transaction_pool: Arc::new(Mutex::new(TransactionPool::new())),
-            is_mining: Arc::new(RwLock::new(false)),
+            chain: None,
         })
     }
 
@@ -568,19 +869,21 @@ impl Node {
 
         let response = client.find_node(request).await?.into_inner();
 
-        let mut routing_table = self
-            .routing_table
-            .write()
-            .map_err(|_| Status::internal("failed to acquire lock on routing table"))?;
-
-        for proto in response.nodes {
-            if let Some(node) = Node::from_sender(&proto) {
-                routing_table.update(node);
+        {
+            let mut routing_table = self
+                .routing_table
+                .write()
+                .map_err(|_| Status::internal("failed to acquire lock on routing table"))?;
+
+            for proto in response.nodes {
+                if let Some(node) = Node::from_sender(&proto) {
+                    routing_table.update(node);
+                }
             }
         }
 
-        drop(routing_table);
         self.sync_blockchain().await;
+        self.sync_transaction_pool().await;
 
         Ok(())
     }
@@ -668,6 +971,140 @@ impl Node {
         Ok(nodes)
     }
 
+    pub async fn announce_block(&self, target: &Node, block: &Block) -> Result<bool, Box<dyn std::error::Error>> {
+        let mut client =
+            LedgerClient::connect(format!("http://{}", target.get_address())).await?;
+
+        let request = Request::new(AnnounceBlockRequest {
+            block: block.to_bytes(),
+        });
+
+        let response = client.announce_block(request).await?.into_inner();
+
+        Ok(response.accepted)
+    }
+
+    pub async fn get_tip(&self, target: &Node) -> Result<Tip, Box<dyn std::error::Error>> {
+        let mut client =
+            LedgerClient::connect(format!("http://{}", target.get_address())).await?;
+
+        let mut stream = client.subscribe_tip(Request::new(SubscribeTipRequest {})).await?.into_inner();
+
+        Ok(stream.message().await?.ok_or("Peer closed the tip stream")?)
+    }
+
+    pub async fn get_headers(
+        &self,
+        target: &Node,
+        locator: Vec<BHash>,
+        count: u32,
+    ) -> Result<Vec<BlockHeader>, Box<dyn std::error::Error>> {
+        let mut client =
+            LedgerClient::connect(format!("http://{}", target.get_address())).await?;
+
+        let request = Request::new(GetHeadersRequest {
+            locator,
+            count,
+        });
+
+        let response = client.get_headers(request).await?.into_inner();
+
+        let headers = response
+            .headers
+            .iter()
+            .map(|bytes| BlockHeader::from_bytes(bytes))
+            .collect::<Result<_, _>>()?;
+
+        Ok(headers)
+    }
+
+    pub async fn get_blocks(
+        &self,
+        target: &Node,
+        start: usize,
+        count: usize,
+    ) -> Result<Vec<Block>, Box<dyn std::error::Error>> {
+        let mut client =
+            LedgerClient::connect(format!("http://{}", target.get_address())).await?;
+
+        let request = Request::new(GetBlocksRequest {
+            start: start as u32,
+            count: count as u32,
+        });
+
+        let response = client.get_blocks(request).await?.into_inner();
+
+        let blocks = response
+            .blocks
+            .iter()
+            .map(|bytes| Block::from_bytes(bytes))
+            .collect::<Result<_, _>>()?;
+
+        Ok(blocks)
+    }
+
+    pub async fn announce_transaction(&self, target: &Node, tx: &Transaction) -> Result<bool, Box<dyn std::error::Error>> {
+        let mut client =
+            LedgerClient::connect(format!("http://{}", target.get_address())).await?;
+
+        let request = Request::new(AnnounceTransactionRequest {
+            transaction: tx.to_bytes(),
+        });
+
+        let response = client.announce_transaction(request).await?.into_inner();
+
+        Ok(response.accepted)
+    }
+
+    pub async fn announce_inventory(
+        &self,
+        target: &Node,
+        tx_hashes: Vec<TxHash>,
+    ) -> Result<Vec<TxHash>, Box<dyn std::error::Error>> {
+        let mut client =
+            LedgerClient::connect(format!("http://{}", target.get_address())).await?;
+
+        let request = Request::new(AnnounceInventoryRequest {
+            tx_hashes,
+        });
+
+        let response = client.announce_inventory(request).await?.into_inner();
+
+        Ok(response.wanted)
+    }
+
+    pub async fn get_mempool(&self, target: &Node) -> Result<Vec<TxHash>, Box<dyn std::error::Error>> {
+        let mut client =
+            LedgerClient::connect(format!("http://{}", target.get_address())).await?;
+
+        let response = client.get_mempool(Request::new(GetMempoolRequest {})).await?.into_inner();
+
+        Ok(response.tx_hashes)
+    }
+
+    pub async fn get_transactions(
+        &self,
+        target: &Node,
+        tx_hashes: Vec<TxHash>,
+    ) -> Result<Vec<Transaction>, Box<dyn std::error::Error>> {
+        let mut client =
+            LedgerClient::connect(format!("http://{}", target.get_address())).await?;
+
+        let request = Request::new(GetTransactionsRequest {
+            tx_hashes,
+        });
+
+        let response = client.get_transactions(request).await?.into_inner();
+
+        let transactions = response
+            .transactions
+            .iter()
+            .map(|bytes| Transaction::from_bytes(bytes))
+            .collect::<Result<_, _>>()?;
+
+        Ok(transactions)
+    }
+
     pub async fn find_value(
         &self,
         target: Node,
@@ -730,20 +1167,19 @@ impl Node {
             .map(move |node| {
                 let routing_table_lock = routing_table_lock.clone();
                 async move {
-                    match tokio::time::timeout(Duration::from_secs(5), self.ping(&node)).await {
-                        Ok(Ok(true)) => match routing_table_lock.write() {
-                            Ok(mut routing_table) => {
-                                routing_table.update(node.clone());
-                            }
-                            Err(_) => {}
-                        },
-                        _ => {}
+                    if let Ok(Ok(true)) =
+                        tokio::time::timeout(Duration::from_secs(5), self.ping(&node)).await
+                    {
+                        if let Ok(mut routing_table) = routing_table_lock.write() {
+                            routing_table.update(node.clone());
+                        }
                     }
                 }
             });
 
         futures::future::join_all(ping_futures).await;
         self.sync_blockchain().await;
+        self.sync_transaction_pool().await;
         Ok(())
     }
 
@@ -855,8 +1291,12 @@ impl Node {
     }
 
     pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let rate_limiter = Arc::new(RateLimiter::new());
         Server::builder()
             .add_service(KademliaServer::new(KademliaService::new(self.clone())))
+            .add_service(LedgerServer::new(LedgerService::new(self.clone(), rate_limiter.clone())))
+            .add_service(LightServer::new(LightService::new(self.clone(), rate_limiter)))
+            .add_service(MiningServer::new(MiningService::new(self.clone())))
             .serve(self.address)
             .await?;
 
@@ -1,7 +1,7 @@
 use crate::constants::{DIFFICULTY, ID_LENGTH, K, KEY_LENGTH};
 use crate::kademlia::kademlia_proto::kademlia_server::Kademlia;
 use crate::kademlia::kademlia_proto::{FindNodeRequest, FindNodeResponse, FindValueRequest, FindValueResponse, JoinRequest, JoinResponse, Node as ProtoNode, PingRequest, PingResponse, ShutdownRequest, ShutdownResponse, StoreRequest, StoreResponse};
-use crate::kademlia::node::{BlockchainMessage, Node};
+use crate::kademlia::node::Node;
 use std::sync::Arc;
 use tokio::sync::Notify;
 use tonic::{Request, Response, Status};
@@ -73,19 +73,11 @@ impl Kademlia for KademliaService {
             Status::invalid_argument("KEY length must be 160 bits (20 bytes)")
         })?;
 
-        if let Some(response_data) = self.node.handle_blockchain_message(&value).await {
-            let storage_lock = self.node.get_storage();
-            let mut storage = storage_lock.write().map_err(|_| {
-                Status::internal("failed to acquire lock on storage")
-            })?;
-            storage.insert(key, response_data);
-        } else {
-            let storage_lock = self.node.get_storage();
-            let mut storage = storage_lock.write().map_err(|_| {
-                Status::internal("failed to acquire lock on storage")
-            })?;
-            storage.insert(key, value);
-        }
+        let storage_lock = self.node.get_storage();
+        let mut storage = storage_lock.write().map_err(|_| {
+            Status::internal("failed to acquire lock on storage")
+        })?;
+        storage.insert(key, value);
 
         Ok(Response::new(StoreResponse {
             success: true,
@@ -180,28 +172,6 @@ impl Kademlia for KademliaService {
             nodes
         };
 
-        tokio::spawn({
-            let node = self.node.clone();
-            let sender_node = sender.clone();
-            async move {
-                let blockchain = node.get_blockchain().read().unwrap().clone();
-                let message = BlockchainMessage::ResponseFullBlockchain { blockchain };
-
-                if let Ok(data) = serde_json::to_vec(&message) {
-                    let blockchain_key = {
-                        use sha2::{Digest, Sha256};
-                        let mut hasher = Sha256::new();
-                        hasher.update(b"initial_blockchain");
-                        hasher.update(sender_node.get_id());
-                        let hash = hasher.finalize();
-                        hash[..KEY_LENGTH].try_into().unwrap_or([0; KEY_LENGTH])
-                    };
-
-                    let _ = node.store(blockchain_key, data).await;
-                }
-            }
-        });
-
         Ok(Response::new(JoinResponse {
             accepted: true,
             closest_nodes,
@@ -0,0 +1,6 @@
+pub mod rate_limiter;
+pub mod service;
+
+pub mod ledger_proto {
+    tonic::include_proto!("ledger");
+}
@@ -0,0 +1,66 @@
+use crate::constants::{GOSSIP_RATE_LIMIT, GOSSIP_RATE_WINDOW, MAX_RATE_LIMITED_PEERS, SYNC_RATE_LIMIT, SYNC_RATE_WINDOW};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tonic::Request;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestKind {
+    Gossip,
+    Sync,
+}
+
+impl RequestKind {
+    fn limit(&self) -> (u32, Duration) {
+        match self {
+            RequestKind::Gossip => (GOSSIP_RATE_LIMIT, GOSSIP_RATE_WINDOW),
+            RequestKind::Sync => (SYNC_RATE_LIMIT, SYNC_RATE_WINDOW),
+        }
+    }
+}
+
+struct RateWindow {
+    start: Instant,
+    requests: u32,
+}
+
+#[derive(Default)]
+pub struct RateLimiter {
+    windows: Mutex<HashMap<(Option<IpAddr>, RequestKind), RateWindow>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allow<T>(&self, request: &Request<T>, kind: RequestKind) -> bool {
+        let peer = request.remote_addr().map(|address| address.ip());
+        let Ok(mut windows) = self.windows.lock() else {
+            return false;
+        };
+
+        if windows.len() >= MAX_RATE_LIMITED_PEERS && !windows.contains_key(&(peer, kind)) {
+            windows.retain(|(_, other), window| window.start.elapsed() < other.limit().1);
+        }
+
+        let (limit, duration) = kind.limit();
+        let window = windows.entry((peer, kind)).or_insert_with(|| RateWindow {
+            start: Instant::now(),
+            requests: 0,
+        });
+
+        if window.start.elapsed() >= duration {
+            window.start = Instant::now();
+            window.requests = 0;
+        }
+
+        if window.requests >= limit {
+            return false;
+        }
+
+        window.requests += 1;
+        true
+    }
+}
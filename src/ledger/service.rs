@@ -0,0 +1,204 @@
+use crate::blockchain::block::Block;
+use crate::blockchain::encoding::{Decode, Encode};
+use crate::blockchain::transaction::Transaction;
+use crate::constants::{MAX_BLOCKS_PER_REQUEST, MAX_HEADERS_PER_REQUEST, MAX_TRANSACTIONS_PER_REQUEST};
+use crate::kademlia::node::Node;
+use crate::ledger::ledger_proto::ledger_server::Ledger;
+use crate::ledger::rate_limiter::{RateLimiter, RequestKind};
+use crate::ledger::ledger_proto::{
+    AnnounceBlockRequest, AnnounceBlockResponse, AnnounceInventoryRequest, AnnounceInventoryResponse,
+    AnnounceTransactionRequest, AnnounceTransactionResponse, GetBlocksRequest, GetBlocksResponse, GetHeadersRequest,
+    GetHeadersResponse, GetMempoolRequest, GetMempoolResponse, GetTransactionsRequest, GetTransactionsResponse,
+    SubscribeTipRequest, Tip,
+};
+use futures::Stream;
+use std::pin::Pin;
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+
+pub struct LedgerService {
+    node: Node,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl LedgerService {
+    pub fn new(node: Node, rate_limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            node,
+            rate_limiter,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Ledger for LedgerService {
+    async fn announce_block(&self, request: Request<AnnounceBlockRequest>) -> Result<Response<AnnounceBlockResponse>, Status> {
+        if !self.rate_limiter.allow(&request, RequestKind::Gossip) {
+            return Err(Status::resource_exhausted("gossip rate limit exceeded"));
+        }
+
+        let block = Block::from_bytes(&request.into_inner().block).map_err(Status::invalid_argument)?;
+
+        let response = match self.node.receive_announced_block(block).await {
+            Ok(accepted) => AnnounceBlockResponse {
+                accepted,
+                error: String::new(),
+            },
+            Err(e) => AnnounceBlockResponse {
+                accepted: false,
+                error: e.to_string(),
+            },
+        };
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_blocks(&self, request: Request<GetBlocksRequest>) -> Result<Response<GetBlocksResponse>, Status> {
+        if !self.rate_limiter.allow(&request, RequestKind::Sync) {
+            return Err(Status::resource_exhausted("sync rate limit exceeded"));
+        }
+
+        let GetBlocksRequest { start, count } = request.into_inner();
+
+        let blockchain_lock = self.node.get_blockchain().map_err(Status::failed_precondition)?;
+        let blockchain = blockchain_lock.read().map_err(|_| {
+            Status::internal("failed to acquire lock on blockchain")
+        })?;
+
+        let blocks = blockchain
+            .get_blocks_range(start as usize, (count as usize).min(MAX_BLOCKS_PER_REQUEST))
+            .iter()
+            .map(|block| block.to_bytes())
+            .collect();
+
+        Ok(Response::new(GetBlocksResponse {
+            blocks,
+        }))
+    }
+
+    async fn get_headers(&self, request: Request<GetHeadersRequest>) -> Result<Response<GetHeadersResponse>, Status> {
+        if !self.rate_limiter.allow(&request, RequestKind::Sync) {
+            return Err(Status::resource_exhausted("sync rate limit exceeded"));
+        }
+
+        let GetHeadersRequest { locator, count } = request.into_inner();
+
+        let blockchain_lock = self.node.get_blockchain().map_err(Status::failed_precondition)?;
+        let blockchain = blockchain_lock.read().map_err(|_| {
+            Status::internal("failed to acquire lock on blockchain")
+        })?;
+
+        let start = blockchain.find_fork_point(&locator).unwrap_or(0) + 1;
+        let headers = blockchain
+            .get_headers_range(start, count.min(MAX_HEADERS_PER_REQUEST) as usize)
+            .iter()
+            .map(|header| header.to_bytes())
+            .collect();
+
+        Ok(Response::new(GetHeadersResponse {
+            headers,
+        }))
+    }
+
+    async fn announce_transaction(&self, request: Request<AnnounceTransactionRequest>) -> Result<Response<AnnounceTransactionResponse>, Status> {
+        if !self.rate_limiter.allow(&request, RequestKind::Gossip) {
+            return Err(Status::resource_exhausted("gossip rate limit exceeded"));
+        }
+
+        let tx = Transaction::from_bytes(&request.into_inner().transaction).map_err(Status::invalid_argument)?;
+
+        let response = match self.node.submit_transaction(tx).await {
+            Ok(()) => AnnounceTransactionResponse {
+                accepted: true,
+                error: String::new(),
+            },
+            Err(e) => AnnounceTransactionResponse {
+                accepted: false,
+                error: e.to_string(),
+            },
+        };
+
+        Ok(Response::new(response))
+    }
+
+    async fn announce_inventory(&self, request: Request<AnnounceInventoryRequest>) -> Result<Response<AnnounceInventoryResponse>, Status> {
+        if !self.rate_limiter.allow(&request, RequestKind::Gossip) {
+            return Err(Status::resource_exhausted("gossip rate limit exceeded"));
+        }
+
+        let AnnounceInventoryRequest { tx_hashes } = request.into_inner();
+
+        let pool_lock = self.node.get_transaction_pool().map_err(Status::failed_precondition)?;
+        let pool = pool_lock.lock().map_err(|_| {
+            Status::internal("failed to acquire lock on transaction pool")
+        })?;
+
+        let wanted = tx_hashes
+            .into_iter()
+            .filter(|tx_hash| !pool.has_seen(tx_hash))
+            .take(MAX_TRANSACTIONS_PER_REQUEST)
+            .collect();
+
+        Ok(Response::new(AnnounceInventoryResponse {
+            wanted,
+        }))
+    }
+
+    async fn get_transactions(&self, request: Request<GetTransactionsRequest>) -> Result<Response<GetTransactionsResponse>, Status> {
+        if !self.rate_limiter.allow(&request, RequestKind::Gossip) {
+            return Err(Status::resource_exhausted("gossip rate limit exceeded"));
+        }
+
+        let GetTransactionsRequest { tx_hashes } = request.into_inner();
+
+        let pool_lock = self.node.get_transaction_pool().map_err(Status::failed_precondition)?;
+        let pool = pool_lock.lock().map_err(|_| {
+            Status::internal("failed to acquire lock on transaction pool")
+        })?;
+
+        let transactions = tx_hashes
+            .iter()
+            .take(MAX_TRANSACTIONS_PER_REQUEST)
+            .filter_map(|tx_hash| pool.get_transaction(tx_hash))
+            .map(|tx| tx.to_bytes())
+            .collect();
+
+        Ok(Response::new(GetTransactionsResponse {
+            transactions,
+        }))
+    }
+
+    async fn get_mempool(&self, request: Request<GetMempoolRequest>) -> Result<Response<GetMempoolResponse>, Status> {
+        if !self.rate_limiter.allow(&request, RequestKind::Gossip) {
+            return Err(Status::resource_exhausted("gossip rate limit exceeded"));
+        }
+
+        let pool_lock = self.node.get_transaction_pool().map_err(Status::failed_precondition)?;
+        let pool = pool_lock.lock().map_err(|_| {
+            Status::internal("failed to acquire lock on transaction pool")
+        })?;
+
+        Ok(Response::new(GetMempoolResponse {
+            tx_hashes: pool.get_inventory(),
+        }))
+    }
+
+    type SubscribeTipStream = Pin<Box<dyn Stream<Item = Result<Tip, Status>> + Send>>;
+
+    async fn subscribe_tip(&self, request: Request<SubscribeTipRequest>) -> Result<Response<Self::SubscribeTipStream>, Status> {
+        if !self.rate_limiter.allow(&request, RequestKind::Gossip) {
+            return Err(Status::resource_exhausted("gossip rate limit exceeded"));
+        }
+
+        let mut tip = self.node.subscribe_tip().map_err(Status::failed_precondition)?;
+        tip.mark_changed();
+
+        let stream = futures::stream::unfold(tip, |mut tip| async move {
+            tip.changed().await.ok()?;
+            let current = tip.borrow_and_update().clone();
+            Some((Ok(current), tip))
+        });
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
@@ -1,4 +1,9 @@
+pub mod api;
 pub mod auctions;
+pub mod cli;
 pub mod constants;
 pub mod kademlia;
+pub mod ledger;
+pub mod light;
+pub mod mining;
 pub mod blockchain;
@@ -0,0 +1,5 @@
+pub mod service;
+
+pub mod light_proto {
+    tonic::include_proto!("light");
+}
@@ -0,0 +1,68 @@
+use crate::blockchain::encoding::Encode;
+use crate::constants::MAX_HEADERS_PER_REQUEST;
+use crate::kademlia::node::Node;
+use crate::ledger::rate_limiter::{RateLimiter, RequestKind};
+use crate::light::light_proto::light_server::Light;
+use crate::light::light_proto::{GetAccountProofRequest, GetAccountProofResponse, GetHeadersRequest, GetHeadersResponse};
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+
+pub struct LightService {
+    node: Node,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl LightService {
+    pub fn new(node: Node, rate_limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            node,
+            rate_limiter,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Light for LightService {
+    async fn get_headers(&self, request: Request<GetHeadersRequest>) -> Result<Response<GetHeadersResponse>, Status> {
+        if !self.rate_limiter.allow(&request, RequestKind::Sync) {
+            return Err(Status::resource_exhausted("sync rate limit exceeded"));
+        }
+
+        let GetHeadersRequest { start, count } = request.into_inner();
+
+        let blockchain_lock = self.node.get_blockchain().map_err(Status::failed_precondition)?;
+        let blockchain = blockchain_lock.read().map_err(|_| {
+            Status::internal("failed to acquire lock on blockchain")
+        })?;
+
+        let headers = blockchain
+            .get_headers_range(start as usize, count.min(MAX_HEADERS_PER_REQUEST) as usize)
+            .iter()
+            .map(|header| header.to_bytes())
+            .collect();
+
+        Ok(Response::new(GetHeadersResponse {
+            headers,
+        }))
+    }
+
+    async fn get_account_proof(&self, request: Request<GetAccountProofRequest>) -> Result<Response<GetAccountProofResponse>, Status> {
+        if !self.rate_limiter.allow(&request, RequestKind::Sync) {
+            return Err(Status::resource_exhausted("sync rate limit exceeded"));
+        }
+
+        let GetAccountProofRequest { address, height } = request.into_inner();
+
+        let blockchain_lock = self.node.get_blockchain().map_err(Status::failed_precondition)?;
+        let blockchain = blockchain_lock.read().map_err(|_| {
+            Status::internal("failed to acquire lock on blockchain")
+        })?;
+
+        let proof = blockchain.get_account_proof(&address, height as usize)
+            .ok_or_else(|| Status::not_found("account not found at the requested height"))?;
+
+        Ok(Response::new(GetAccountProofResponse {
+            proof: proof.to_bytes(),
+        }))
+    }
+}
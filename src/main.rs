@@ -1,12 +1,26 @@
 use ed25519_dalek::Keypair;
+use ledger::api;
 use ledger::auctions::auction::{collect_auctions, find_auction_transactions, Auction, AuctionStatus};
 use ledger::auctions::auction_commands::{generate_auction_id, tx_bid, tx_create_auction, tx_end_auction, tx_start_auction, AuctionCommand};
-use ledger::blockchain::blockchain::Blockchain;
-use ledger::blockchain::transaction::TransactionType;
-use ledger::constants::DIFFICULTY;
+use ledger::blockchain::block::BlockHeader;
+use ledger::blockchain::blockchain::{Blockchain, LightClient};
+use ledger::blockchain::encoding::Decode;
+use ledger::blockchain::genesis::GenesisConfig;
+use ledger::blockchain::state::AccountProof;
+use ledger::cli::commands;
+use ledger::constants::{API_PORT_OFFSET, DIFFICULTY, MAX_HEADERS_PER_REQUEST};
 use ledger::kademlia::kademlia_proto::kademlia_server::KademliaServer;
 use ledger::kademlia::node::Node;
 use ledger::kademlia::service::KademliaService;
+use ledger::ledger::ledger_proto::ledger_server::LedgerServer;
+use ledger::ledger::rate_limiter::RateLimiter;
+use ledger::ledger::service::LedgerService;
+use ledger::light::light_proto::light_client::LightClient as LightRpcClient;
+use ledger::light::light_proto::light_server::LightServer;
+use ledger::light::light_proto::{GetAccountProofRequest, GetHeadersRequest};
+use ledger::light::service::LightService;
+use ledger::mining::mining_proto::mining_server::MiningServer;
+use ledger::mining::service::MiningService;
 use std::collections::HashMap;
 use std::env;
 use std::io::{self, Write};
@@ -15,24 +29,41 @@ use std::str::FromStr;
 use std::sync::Arc;
 use tokio::io::{self as tokio_io, AsyncBufReadExt};
 use tokio::sync::Notify;
-use tonic::transport::Server;
+use tonic::transport::{Channel, Server};
+use tonic::Request;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 3 {
-        println!("Usage: cargo run <SELF PORT> <BOOTSTRAP PORT>");
-        return Ok(());
+    let command: Vec<&str> = args.iter().skip(1).map(String::as_str).collect();
+
+    match command.as_slice() {
+        ["light", port] => light_client(port.parse()?).await,
+        ["node", "run", port, bootstrap_port] => run_node(port.parse()?, bootstrap_port.parse()?, false).await,
+        [port, bootstrap_port] if port.parse::<u16>().is_ok() => run_node(port.parse()?, bootstrap_port.parse()?, true).await,
+        [] => {
+            println!("{}", commands::USAGE);
+            Ok(())
+        }
+        _ => {
+            if let Err(e) = commands::run(&args[1..]).await {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            Ok(())
+        }
     }
+}
 
+async fn run_node(port: u16, bootstrap_port: u16, interactive: bool) -> Result<(), Box<dyn std::error::Error>> {
     let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
-    let port: u16 = args[1].parse()?;
-    let bootstrap_port: u16 = args[2].parse()?;
     let difficulty: usize = DIFFICULTY;
 
     let address = SocketAddr::new(ip, port);
     let bootstrap_address = SocketAddr::new(ip, bootstrap_port);
+    let api_address = SocketAddr::new(ip, port.checked_add(API_PORT_OFFSET).ok_or("Port is too high for the HTTP API")?);
 
+    GenesisConfig::init()?;
     let node = Node::new(address);
     let shutdown = Arc::new(Notify::new());
     let shutdown_trigger = shutdown.clone();
@@ -44,22 +75,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     if bootstrap_address == address {
         println!("[BOOTSTRAP] Listening on {}", address);
     }
+    println!("[NODE {}] Chain ID: {}", address.port(), node.get_chain_id()?);
+    println!("[NODE {}] HTTP API on {}", address.port(), api_address);
 
+    let rate_limiter = Arc::new(RateLimiter::new());
     let server = Server::builder()
         .add_service(KademliaServer::new(service))
+        .add_service(LedgerServer::new(LedgerService::new(node.clone(), rate_limiter.clone())))
+        .add_service(LightServer::new(LightService::new(node.clone(), rate_limiter)))
+        .add_service(MiningServer::new(MiningService::new(node.clone())))
         .serve_with_shutdown(address, async move {
             shutdown_trigger.notified().await;
         });
 
-    tokio::select! {
-        result = server => result?,
-        result = menu(node.clone(), ip, address, bootstrap_address,difficulty, keypair, nonce) => result?,
+    if interactive {
+        tokio::select! {
+            result = server => result?,
+            result = api::service::serve(node.clone(), api_address) => result?,
+            result = menu(node.clone(), ip, address, bootstrap_address,difficulty, keypair, nonce) => result?,
+        }
+    } else {
+        tokio::select! {
+            result = server => result?,
+            result = api::service::serve(node.clone(), api_address) => result?,
+            result = run_headless(node.clone(), bootstrap_address, difficulty) => result?,
+        }
     }
 
     println!("Node {} shutting down", address.port());
     Ok(())
 }
 
+async fn run_headless(
+    node: Node,
+    bootstrap_address: SocketAddr,
+    difficulty: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+
+    node.join(Node::new_peer(bootstrap_address), difficulty).await?;
+    println!("[NODE {}] Joined the network", node.get_address().port());
+
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
+
 async fn menu(
     node: Node,
     ip: IpAddr,
@@ -71,7 +131,7 @@ async fn menu(
 ) -> Result<(), Box<dyn std::error::Error>> {
     tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
 
-    let bootstrap_node = Node::new(bootstrap_address);
+    let bootstrap_node = Node::new_peer(bootstrap_address);
     node.join(bootstrap_node.clone(), difficulty).await?;
 
     let stdin = tokio_io::BufReader::new(tokio_io::stdin());
@@ -105,18 +165,106 @@ async fn menu(
             "2" => handle_store(&node).await?,
             "3" => handle_find_node(&node, ip).await?,
             "4" => handle_find_value(&node, ip).await?,
-            "5" => handle_whoami(&node, &keypair),
+            "5" => handle_whoami(&node, &keypair)?,
             "6" => handle_create_auction(&node, &keypair, nonce.clone()).await?,
             "7" => handle_list_auctions(&node, &keypair, nonce.clone()).await?,
             "8" => handle_list_my_auctions(&node, &keypair, nonce.clone()).await?,
             "9" => handle_list_bids(&node).await?,
             "10" => handle_mine_block(&node).await?,
-            "11" => handle_blockchain_info(&node),
+            "11" => handle_blockchain_info(&node)?,
             _ => println!("Invalid option."),
         }
     }
 }
 
+async fn light_client(node_port: u16) -> Result<(), Box<dyn std::error::Error>> {
+    GenesisConfig::init()?;
+    let mut client = LightRpcClient::connect(format!("http://127.0.0.1:{}", node_port)).await?;
+    let mut light_client = LightClient::new();
+
+    sync_headers(&mut client, &mut light_client).await?;
+
+    let stdin = tokio_io::BufReader::new(tokio_io::stdin());
+    let mut lines = stdin.lines();
+
+    loop {
+        println!("\n=== LIGHT CLIENT {} MENU ===", node_port);
+        println!("0. EXIT");
+        println!("1. SYNC HEADERS");
+        println!("2. CHECK BALANCE");
+        print!("\nOption: ");
+        io::stdout().flush().unwrap();
+
+        let input = match lines.next_line().await? {
+            Some(line) => line.trim().to_string(),
+            None => return Ok(()),
+        };
+
+        match input.as_str() {
+            "0" => return Ok(()),
+            "1" => sync_headers(&mut client, &mut light_client).await?,
+            "2" => handle_light_balance(&mut client, &mut light_client).await?,
+            _ => println!("Invalid option."),
+        }
+    }
+}
+
+async fn sync_headers(
+    client: &mut LightRpcClient<Channel>,
+    light_client: &mut LightClient,
+) -> Result<(), Box<dyn std::error::Error>> {
+    loop {
+        let request = Request::new(GetHeadersRequest {
+            start: light_client.get_height() as u32,
+            count: MAX_HEADERS_PER_REQUEST,
+        });
+
+        let headers = client.get_headers(request).await?.into_inner().headers;
+        if headers.is_empty() {
+            break;
+        }
+
+        for header in headers {
+            light_client.add_header(BlockHeader::from_bytes(&header)?)?;
+        }
+    }
+
+    println!("Synced headers: {} blocks", light_client.get_height());
+    Ok(())
+}
+
+async fn handle_light_balance(
+    client: &mut LightRpcClient<Channel>,
+    light_client: &mut LightClient,
+) -> Result<(), Box<dyn std::error::Error>> {
+    sync_headers(client, light_client).await?;
+
+    let address = prompt_hex("Address (hex): ").await;
+    let height = light_client.get_height().saturating_sub(1);
+
+    let request = Request::new(GetAccountProofRequest {
+        address: address.clone(),
+        height: height as u32,
+    });
+
+    let proof = match client.get_account_proof(request).await {
+        Ok(response) => AccountProof::from_bytes(&response.into_inner().proof)?,
+        Err(e) => {
+            println!("No account found: {}", e.message());
+            return Ok(());
+        }
+    };
+
+    if light_client.verify_balance(&address, &proof, height) {
+        println!("Balance at block {}: {}", height, proof.account.balance);
+        println!("Nonce: {}", proof.account.nonce);
+    } else {
+        println!("Account proof verification failed.");
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 struct BidInfo {
     amount: u64,
@@ -130,7 +278,7 @@ async fn handle_list_bids(
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== AUCTION BIDS VIEWER ===");
 
-    let blockchain = node.get_blockchain();
+    let blockchain = node.get_blockchain()?;
     let blockchain_data = {
         let guard = blockchain.read().unwrap();
         (*guard).clone()
@@ -174,30 +322,16 @@ async fn handle_list_bids(
 fn extract_all_bids(blockchain: &Blockchain) -> HashMap<String, Vec<BidInfo>> {
     let mut bid_data: HashMap<String, Vec<BidInfo>> = HashMap::new();
 
-    for block in &blockchain.blocks {
-        for tx in &block.transactions {
-            if tx.data.tx_type != TransactionType::Data {
-                continue;
-            }
+    for tx in find_auction_transactions(blockchain) {
+        if let Some(AuctionCommand::Bid { id, amount }) = tx.get_auction_command() {
+            let bid = BidInfo {
+                amount,
+                bidder: tx.data.sender.clone(),
+                timestamp: tx.data.timestamp,
+                tx_hash: tx.tx_hash.clone(),
+            };
 
-            if let Some(data) = &tx.data.data {
-                if data.starts_with("AUCTION_") {
-                    if let Some(stripped) = data.strip_prefix("AUCTION_") {
-                        if let Ok(command) = serde_json::from_str::<AuctionCommand>(stripped) {
-                            if let AuctionCommand::Bid { id, amount } = command {
-                                let bid = BidInfo {
-                                    amount,
-                                    bidder: tx.data.sender.clone(),
-                                    timestamp: tx.data.timestamp,
-                                    tx_hash: tx.tx_hash.clone(),
-                                };
-
-                                bid_data.entry(id).or_insert_with(Vec::new).push(bid);
-                            }
-                        }
-                    }
-                }
-            }
+            bid_data.entry(id).or_insert_with(Vec::new).push(bid);
         }
     }
 
@@ -363,7 +497,7 @@ async fn handle_view_bids_from_submenu(
 
     println!("\nAvailable auctions:");
 
-    let blockchain = node.get_blockchain();
+    let blockchain = node.get_blockchain()?;
     let blockchain_data = {
         let guard = blockchain.read().unwrap();
         (*guard).clone()
@@ -398,7 +532,7 @@ async fn handle_mine_block(node: &Node) -> Result<(), Box<dyn std::error::Error>
     println!("\n[NODE {}] MINING BLOCK...", node.get_address().port());
 
     let pool_size = {
-        let pool = node.get_transaction_pool();
+        let pool = node.get_transaction_pool()?;
         let pool_guard = pool.lock().unwrap();
         pool_guard.size()
     };
@@ -408,7 +542,7 @@ async fn handle_mine_block(node: &Node) -> Result<(), Box<dyn std::error::Error>
     } else {
         println!("[NODE {}] Mining block with {} pending transactions", node.get_address().port(), pool_size);
 
-        let pool = node.get_transaction_pool();
+        let pool = node.get_transaction_pool()?;
         let pool_guard = pool.lock().unwrap();
         let transactions = pool_guard.get_all_transactions();
 
@@ -434,12 +568,17 @@ async fn handle_mine_block(node: &Node) -> Result<(), Box<dyn std::error::Error>
                      node.get_address().port(), block.index);
             println!("Block Hash: {}", hex::encode(&block.hash[..8]));
             println!("Nonce: {}", block.nonce);
+            println!("Bits: {:08x}", block.bits);
             println!("Mining Time: {:.2}s", mining_time.as_secs_f64());
             println!("Transactions in Block: {}", block.transactions.len());
 
-            if block.transactions.len() > 0 {
+            if let Some(coinbase) = block.transactions.first().filter(|tx| tx.is_coinbase()) {
+                println!("Block Reward: {}", coinbase.data.amount.unwrap_or(0));
+            }
+
+            if block.transactions.len() > 1 {
                 println!("Block Contents:");
-                for (i, tx) in block.transactions.iter().enumerate() {
+                for (i, tx) in block.transactions.iter().enumerate().skip(1) {
                     if let Some(data) = &tx.data.data {
                         if data.starts_with("AUCTION_") {
                             if data.contains("CreateAuction") {
@@ -465,23 +604,30 @@ async fn handle_mine_block(node: &Node) -> Result<(), Box<dyn std::error::Error>
     Ok(())
 }
 
-fn handle_blockchain_info(node: &Node) {
+fn handle_blockchain_info(node: &Node) -> Result<(), &'static str> {
     println!("\n[NODE {}] BLOCKCHAIN STATUS", node.get_address().port());
 
-    let (height, last_hash) = node.get_blockchain_info();
+    let (height, last_hash) = node.get_blockchain_info()?;
+    println!("Chain ID: {}", node.get_chain_id()?);
     println!("Chain Height: {} blocks", height);
 
     if let Some(hash) = last_hash {
         println!("Last Block Hash: {}", &hash[..16]);
     }
 
-    let pool = node.get_transaction_pool();
-    let pool_guard = pool.lock().unwrap();
-    let pool_size = pool_guard.size();
+    let pool_size = {
+        let pool = node.get_transaction_pool()?;
+        let pool_guard = pool.lock().unwrap();
+        pool_guard.size()
+    };
     println!("Transaction Pool: {} pending transactions", pool_size);
 
-    let blockchain = node.get_blockchain();
+    let blockchain = node.get_blockchain()?;
     let blockchain_guard = blockchain.read().unwrap();
+    println!("My Balance: {}", blockchain_guard.get_balance(&node.get_public_key().to_vec()));
+    println!("Next Bits: {:08x}", blockchain_guard.get_next_bits());
+    println!("Finalized Height: {}", blockchain_guard.get_finalized_height());
+
     let recent_blocks = if blockchain_guard.blocks.len() >= 3 {
         &blockchain_guard.blocks[blockchain_guard.blocks.len() - 3..]
     } else {
@@ -495,11 +641,13 @@ fn handle_blockchain_info(node: &Node) {
                  block.transactions.len(),
                  hex::encode(&block.hash[..8]));
     }
+
+    Ok(())
 }
 
 async fn handle_ping(node: &Node, ip: IpAddr) -> Result<(), Box<dyn std::error::Error>> {
     let port: u16 = prompt_parse("Target Port: ").await;
-    let target = Node::new(SocketAddr::new(ip, port));
+    let target = Node::new_peer(SocketAddr::new(ip, port));
     match node.ping(&target).await {
         Ok(ok) => println!("Node {}:{} is alive: {}", ip, port, ok),
         Err(e) => eprintln!("Node {}:{} is not alive: {}", ip, port, e),
@@ -523,7 +671,7 @@ async fn handle_store(node: &Node) -> Result<(), Box<dyn std::error::Error>> {
 async fn handle_find_node(node: &Node, ip: IpAddr) -> Result<(), Box<dyn std::error::Error>> {
     let id = prompt_hex("Target ID (40 hex chars): ").await;
     let port: u16 = prompt_parse("Target Port: ").await;
-    let target = Node::new(SocketAddr::new(ip, port));
+    let target = Node::new_peer(SocketAddr::new(ip, port));
     match id.try_into() {
         Ok(id_array) => {
             match node.find_node(target, id_array).await {
@@ -545,7 +693,7 @@ async fn handle_find_node(node: &Node, ip: IpAddr) -> Result<(), Box<dyn std::er
 async fn handle_find_value(node: &Node, ip: IpAddr) -> Result<(), Box<dyn std::error::Error>> {
     let key = prompt_hex("Key (40 hex chars): ").await;
     let port: u16 = prompt_parse("Target Port: ").await;
-    let target = Node::new(SocketAddr::new(ip, port));
+    let target = Node::new_peer(SocketAddr::new(ip, port));
     match key.try_into() {
         Ok(key_array) => {
             match node.find_value(target, key_array).await {
@@ -570,10 +718,12 @@ async fn handle_find_value(node: &Node, ip: IpAddr) -> Result<(), Box<dyn std::e
     Ok(())
 }
 
-fn handle_whoami(node: &Node, keypair: &Keypair) {
+fn handle_whoami(node: &Node, keypair: &Keypair) -> Result<(), &'static str> {
     println!("Node ID: {:02x?}", node.get_id());
     println!("Address: {}", node.get_address());
+    println!("Chain ID: {}", node.get_chain_id()?);
     println!("Public Key: {:02x?}", keypair.public.to_bytes());
+    Ok(())
 }
 
 async fn handle_create_auction(
@@ -585,12 +735,12 @@ async fn handle_create_auction(
     let description = prompt("Auction Description: ").await;
 
     let correct_nonce = {
-        let blockchain = node.get_blockchain();
+        let blockchain = node.get_blockchain()?;
         let blockchain_guard = blockchain.read().unwrap();
         let blockchain_nonce = blockchain_guard.get_next_nonce(&keypair.public.to_bytes().to_vec());
         drop(blockchain_guard);
 
-        let pool = node.get_transaction_pool();
+        let pool = node.get_transaction_pool()?;
         let pool_guard = pool.lock().unwrap();
         let sender_key = keypair.public.to_bytes().to_vec();
         let pending_txs = pool_guard.get_pending_by_sender(&sender_key);
@@ -600,7 +750,7 @@ async fn handle_create_auction(
         blockchain_nonce + pending_count
     };
 
-    match tx_create_auction(keypair, title.clone(), description.clone(), correct_nonce) {
+    match tx_create_auction(keypair, &node.get_chain_id()?, title.clone(), description.clone(), correct_nonce) {
         Ok(transaction) => {
             let auction_id = generate_auction_id(&keypair.public.to_bytes(), &title, &description, correct_nonce);
 
@@ -628,7 +778,7 @@ async fn handle_list_auctions(
     keypair: &Keypair,
     nonce: Arc<std::sync::Mutex<u64>>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let blockchain = node.get_blockchain();
+    let blockchain = node.get_blockchain()?;
     let blockchain_data = {
         let guard = blockchain.read().unwrap();
         (*guard).clone()
@@ -718,9 +868,9 @@ async fn handle_bid(
                 }
             }
 
-            let correct_nonce = calculate_next_nonce(node, keypair);
+            let correct_nonce = calculate_next_nonce(node, keypair)?;
 
-            match tx_bid(keypair, auction_id.clone(), bid_amount, correct_nonce) {
+            match tx_bid(keypair, &node.get_chain_id()?, auction_id.clone(), bid_amount, correct_nonce) {
                 Ok(transaction) => {
                     match node.submit_transaction(transaction).await {
                         Ok(_) => {
@@ -748,7 +898,7 @@ async fn handle_list_my_auctions(
     keypair: &Keypair,
     nonce: Arc<std::sync::Mutex<u64>>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let blockchain = node.get_blockchain();
+    let blockchain = node.get_blockchain()?;
     let blockchain_data = {
         let guard = blockchain.read().unwrap();
         (*guard).clone()
@@ -850,9 +1000,9 @@ async fn handle_start_auction(
 
     match startable_auctions.get(&auction_id) {
         Some(auction) => {
-            let correct_nonce = calculate_next_nonce(node, keypair);
+            let correct_nonce = calculate_next_nonce(node, keypair)?;
 
-            match tx_start_auction(keypair, auction_id.clone(), correct_nonce) {
+            match tx_start_auction(keypair, &node.get_chain_id()?, auction_id.clone(), correct_nonce) {
                 Ok(transaction) => {
                     match node.submit_transaction(transaction).await {
                         Ok(_) => {
@@ -920,9 +1070,9 @@ async fn handle_end_auction(
 
             let confirm = prompt("Are you sure you want to end this auction? (y/N): ").await;
             if confirm.to_lowercase() == "y" || confirm.to_lowercase() == "yes" {
-                let correct_nonce = calculate_next_nonce(node, keypair);
+                let correct_nonce = calculate_next_nonce(node, keypair)?;
 
-                match tx_end_auction(keypair, auction_id.clone(), correct_nonce) {
+                match tx_end_auction(keypair, &node.get_chain_id()?, auction_id.clone(), correct_nonce) {
                     Ok(transaction) => {
                         match node.submit_transaction(transaction).await {
                             Ok(_) => {
@@ -976,18 +1126,18 @@ async fn prompt_parse<T: FromStr>(msg: &str) -> T {
     }
 }
 
-fn calculate_next_nonce(node: &Node, keypair: &Keypair) -> u64 {
-    let blockchain = node.get_blockchain();
+fn calculate_next_nonce(node: &Node, keypair: &Keypair) -> Result<u64, &'static str> {
+    let blockchain = node.get_blockchain()?;
     let blockchain_guard = blockchain.read().unwrap();
     let blockchain_nonce = blockchain_guard.get_next_nonce(&keypair.public.to_bytes().to_vec());
     drop(blockchain_guard);
 
-    let pool = node.get_transaction_pool();
+    let pool = node.get_transaction_pool()?;
     let pool_guard = pool.lock().unwrap();
     let sender_key = keypair.public.to_bytes().to_vec();
     let pending_txs = pool_guard.get_pending_by_sender(&sender_key);
     let pending_count = pending_txs.len() as u64;
     drop(pool_guard);
 
-    blockchain_nonce + pending_count
+    Ok(blockchain_nonce + pending_count)
 }
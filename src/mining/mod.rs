@@ -0,0 +1,40 @@
+pub mod service;
+
+pub mod mining_proto {
+    tonic::include_proto!("mining");
+}
+
+use crate::blockchain::block::BlockHeader;
+
+impl From<&BlockHeader> for mining_proto::BlockHeader {
+    fn from(header: &BlockHeader) -> Self {
+        mining_proto::BlockHeader {
+            index: header.index,
+            timestamp: header.timestamp as u64,
+            prev_hash: header.prev_hash.clone(),
+            nonce: header.nonce,
+            extra_nonce: header.extra_nonce,
+            bits: header.bits,
+            merkle_root: header.merkle_root.clone(),
+            tx_count: header.tx_count,
+            state_root: header.state_root.clone(),
+        }
+    }
+}
+
+impl From<mining_proto::BlockHeader> for BlockHeader {
+    fn from(header: mining_proto::BlockHeader) -> Self {
+        BlockHeader {
+            index: header.index,
+            timestamp: header.timestamp as u128,
+            hash: vec![0; 32],
+            prev_hash: header.prev_hash,
+            nonce: header.nonce,
+            extra_nonce: header.extra_nonce,
+            bits: header.bits,
+            merkle_root: header.merkle_root,
+            state_root: header.state_root,
+            tx_count: header.tx_count,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::kademlia::node::Node;
+use crate::mining::mining_proto::mining_server::Mining;
+use crate::mining::mining_proto::{GetWorkRequest, GetWorkResponse, SubmitWorkRequest, SubmitWorkResponse};
+use tonic::{Request, Response, Status};
+
+pub struct MiningService {
+    node: Node,
+}
+
+impl MiningService {
+    pub fn new(node: Node) -> Self {
+        Self {
+            node,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Mining for MiningService {
+    async fn get_work(&self, _request: Request<GetWorkRequest>) -> Result<Response<GetWorkResponse>, Status> {
+        let template = self.node.get_block_template().map_err(Status::unavailable)?;
+
+        Ok(Response::new(GetWorkResponse {
+            template_id: template.get_id(),
+            header: Some((&template.get_header()).into()),
+        }))
+    }
+
+    async fn submit_work(&self, request: Request<SubmitWorkRequest>) -> Result<Response<SubmitWorkResponse>, Status> {
+        let request = request.into_inner();
+
+        let result = self.node.submit_block_solution(
+            &request.template_id,
+            request.extra_nonce,
+            request.timestamp as u128,
+            request.nonce,
+        ).await;
+
+        let response = match result {
+            Ok(block) => {
+                println!("\n[MINING] Accepted external solution for block {}", block.index);
+                SubmitWorkResponse {
+                    accepted: true,
+                    block_hash: block.hash,
+                    error: String::new(),
+                }
+            }
+            Err(e) => SubmitWorkResponse {
+                accepted: false,
+                block_hash: Vec::new(),
+                error: e.to_string(),
+            },
+        };
+
+        Ok(Response::new(response))
+    }
+}